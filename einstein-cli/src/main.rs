@@ -0,0 +1,374 @@
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use einstein_puzzle::code::PuzzleCode;
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::minimize::Necessity;
+use einstein_puzzle::puzzle_gen::{
+    generate_batch, rule_necessity, GenerationControl, Minimization,
+};
+use einstein_puzzle::rules::{Propagation, PuzzleSize, Rule, SolvedPuzzle, Topology};
+use einstein_puzzle::solver::solve_limited;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufReader, Read, Write};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Parser)]
+#[command(version, about = "Generate and solve Einstein puzzles")]
+struct Args {
+    /// Number of kinds (rows)
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u8).range(1..=15))]
+    kinds: u8,
+
+    /// Number of values (columns)
+    #[arg(long, default_value_t = 6, value_parser = clap::value_parser!(u8).range(2..=15))]
+    values: u8,
+
+    /// Seat values around a round table: the last column is next to the first one
+    #[arg(long)]
+    ring: bool,
+
+    /// Lay values out in rows of this many houses
+    #[arg(long, value_name = "WIDTH", conflicts_with = "ring", value_parser = clap::value_parser!(u8).range(1..=15))]
+    grid: Option<u8>,
+
+    /// easy, medium, hard or expert
+    #[arg(long, default_value = "medium", value_parser = parse_difficulty)]
+    difficulty: Difficulty,
+
+    /// Seed of the first puzzle; following puzzles use the next seeds
+    #[arg(long)]
+    seed: Option<u64>,
+
+    /// Number of puzzles to generate
+    #[arg(long, default_value_t = 1)]
+    count: usize,
+
+    /// Give up on a puzzle after this many seconds
+    #[arg(long, value_name = "SECONDS")]
+    timeout: Option<f64>,
+
+    /// Search for a smallest set of rules, visiting at most NODES search nodes.
+    /// Such puzzles have no code, since codes reproduce the usual rules
+    #[arg(long, value_name = "NODES", num_args = 0..=1, default_missing_value = "10000")]
+    minimize: Option<usize>,
+
+    /// singles, subsets or arc-consistency: judge the difficulty of minimized
+    /// puzzles for a board doing this much deduction on its own
+    #[arg(long, default_value = "singles", requires = "minimize", value_parser = parse_propagation)]
+    propagation: Propagation,
+
+    /// Tell which rules the puzzle could do without
+    #[arg(long)]
+    necessity: bool,
+
+    /// Report generation progress on standard error
+    #[arg(long)]
+    progress: bool,
+
+    /// Write JSON instead of text
+    #[arg(long)]
+    json: bool,
+
+    /// Solve puzzles from a JSON file ("-" for standard input) instead of generating
+    #[arg(long, value_name = "FILE")]
+    solve: Option<PathBuf>,
+}
+
+fn parse_difficulty(s: &str) -> Result<Difficulty, String> {
+    Difficulty::ALL
+        .into_iter()
+        .find(|difficulty| difficulty.to_string().eq_ignore_ascii_case(s))
+        .ok_or_else(|| format!("unknown difficulty {}", s))
+}
+
+fn parse_propagation(s: &str) -> Result<Propagation, String> {
+    Propagation::ALL
+        .into_iter()
+        .find(|propagation| propagation.to_string().eq_ignore_ascii_case(s))
+        .ok_or_else(|| format!("unknown propagation {}", s))
+}
+
+#[derive(Serialize, Deserialize)]
+struct PuzzleJson {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    size: PuzzleSize,
+    rules: Vec<Rule>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    solution: Option<SolvedPuzzle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    necessity: Option<Vec<Necessity>>,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PuzzlesJson {
+    One(PuzzleJson),
+    Many(Vec<PuzzleJson>),
+}
+
+fn generate(args: &Args) -> Result<Vec<PuzzleJson>> {
+    let size = PuzzleSize {
+        kinds: args.kinds,
+        values: args.values,
+        topology: match args.grid {
+            Some(width) => Topology::Grid(width),
+            None if args.ring => Topology::Ring,
+            None => Topology::Line,
+        },
+    };
+    if !size.is_valid() {
+        bail!("{} values do not fill rows of the grid", size.values);
+    }
+    let first_seed = args.seed.unwrap_or_else(rand::random);
+    let code = |index: usize| {
+        PuzzleCode::new(size, args.difficulty, first_seed.wrapping_add(index as u64))
+            .expect("Command line limits keep sizes within codes")
+    };
+    let to_json = |code: PuzzleCode, (solution, rules): (SolvedPuzzle, Vec<Rule>)| PuzzleJson {
+        code: args.minimize.is_none().then(|| code.to_string()),
+        size,
+        necessity: args
+            .necessity
+            .then(|| rule_necessity(&solution, &rules, args.difficulty)),
+        rules,
+        solution: Some(solution),
+    };
+
+    // puzzles are watched one by one, otherwise they are made on all cores
+    if !args.progress && args.timeout.is_none() && args.minimize.is_none() {
+        let puzzles = generate_batch(size, args.difficulty, args.count, first_seed)?;
+        return Ok(puzzles
+            .into_iter()
+            .enumerate()
+            .map(|(index, puzzle)| to_json(code(index), puzzle))
+            .collect());
+    }
+    (0..args.count)
+        .map(|index| {
+            let code = code(index);
+            let mut control = GenerationControl::new();
+            if let Some(max_nodes) = args.minimize {
+                control = control.with_minimization(Minimization::Smallest {
+                    max_nodes,
+                    propagation: args.propagation,
+                });
+            }
+            if let Some(timeout) = args.timeout {
+                control = control.with_timeout(Duration::from_secs_f64(timeout));
+            }
+            if args.progress {
+                control = control.with_progress(move |progress| {
+                    eprint!(
+                        "\rpuzzle {}, attempt {}: {} rules added, {} removed ",
+                        index + 1,
+                        progress.attempt,
+                        progress.rules_added,
+                        progress.rules_removed
+                    )
+                });
+            }
+            let puzzle = code
+                .generate_with(&mut control)
+                .with_context(|| format!("Cannot generate puzzle {}", code))?;
+            if args.progress {
+                eprintln!();
+            }
+            Ok(to_json(code, puzzle))
+        })
+        .collect()
+}
+
+// Puzzles read from files get the same limits as generated ones.
+fn check_puzzle(puzzle: &PuzzleJson) -> Result<()> {
+    let size = puzzle.size;
+    if !(1..=15).contains(&size.kinds) {
+        bail!("Puzzle has {} kinds, 1 to 15 are supported", size.kinds);
+    }
+    if !(2..=15).contains(&size.values) {
+        bail!("Puzzle has {} values, 2 to 15 are supported", size.values);
+    }
+    if !size.is_valid() {
+        bail!("{} values do not fill rows of the grid", size.values);
+    }
+    if let Some(rule) = puzzle.rules.iter().find(|rule| !rule.fits(size)) {
+        bail!("Rule \"{}\" does not fit the puzzle size", rule);
+    }
+    Ok(())
+}
+
+fn solve(puzzle: PuzzleJson) -> Result<PuzzleJson> {
+    check_puzzle(&puzzle)?;
+    let mut solutions = solve_limited(puzzle.size, &puzzle.rules, 2)?;
+    match solutions.len() {
+        0 => bail!("Puzzle has no solution"),
+        1 => Ok(PuzzleJson {
+            solution: solutions.pop(),
+            necessity: None,
+            ..puzzle
+        }),
+        _ => bail!("Puzzle has more than one solution"),
+    }
+}
+
+fn read_puzzles(path: &PathBuf) -> Result<Vec<PuzzleJson>> {
+    let reader: Box<dyn Read> = if path.as_os_str() == "-" {
+        Box::new(io::stdin())
+    } else {
+        Box::new(File::open(path).with_context(|| format!("Cannot open {}", path.display()))?)
+    };
+    let puzzles = serde_json::from_reader(BufReader::new(reader))?;
+    Ok(match puzzles {
+        PuzzlesJson::One(puzzle) => vec![puzzle],
+        PuzzlesJson::Many(puzzles) => puzzles,
+    })
+}
+
+fn write_solution(out: &mut impl Write, solution: &SolvedPuzzle) -> io::Result<()> {
+    let size = solution.size();
+    for kind in size.iter_kinds() {
+        let rows: Vec<String> = (0..size.grid_rows())
+            .map(|row| {
+                let things: Vec<String> = (0..size.width())
+                    .map(|x| solution.get(kind, row * size.width() + x).to_string())
+                    .collect();
+                things.join(" ")
+            })
+            .collect();
+        writeln!(out, "  {}", rows.join(" | "))?;
+    }
+    Ok(())
+}
+
+fn write_text(out: &mut impl Write, puzzles: &[PuzzleJson]) -> io::Result<()> {
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        if index > 0 {
+            writeln!(out)?;
+        }
+        write!(out, "Puzzle {}", index + 1)?;
+        if let Some(ref code) = puzzle.code {
+            write!(out, " ({})", code)?;
+        }
+        write!(out, ", {}×{}", puzzle.size.values, puzzle.size.kinds)?;
+        match puzzle.size.topology {
+            Topology::Line => {}
+            Topology::Ring => write!(out, ", round table")?,
+            Topology::Grid(width) => write!(out, ", {}×{} grid", width, puzzle.size.grid_rows())?,
+        }
+        writeln!(out)?;
+        for (number, rule) in puzzle.rules.iter().enumerate() {
+            write!(out, "{:3}. {}", number + 1, rule)?;
+            if let Some(Necessity::Redundant) = puzzle
+                .necessity
+                .as_ref()
+                .and_then(|necessity| necessity.get(number))
+            {
+                write!(out, " (redundant)")?;
+            }
+            writeln!(out)?;
+        }
+        if let Some(ref solution) = puzzle.solution {
+            writeln!(out, "Solution:")?;
+            write_solution(out, solution)?;
+        }
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let puzzles = match args.solve {
+        Some(ref path) => read_puzzles(path)?
+            .into_iter()
+            .map(solve)
+            .collect::<Result<Vec<_>>>()?,
+        None => generate(&args)?,
+    };
+
+    let mut out = io::stdout().lock();
+    if args.json {
+        serde_json::to_writer_pretty(&mut out, &puzzles)?;
+        writeln!(out)?;
+    } else {
+        write_text(&mut out, &puzzles)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solve_error(json: &str) -> String {
+        let Err(error) = solve(serde_json::from_str(json).unwrap()) else {
+            panic!("Puzzle is expected to be rejected");
+        };
+        error.to_string()
+    }
+
+    #[test]
+    fn test_solve_rejects_unsupported_size() {
+        assert_eq!(
+            solve_error(r#"{"size":{"kinds":20,"values":6,"topology":"Line"},"rules":[]}"#),
+            "Puzzle has 20 kinds, 1 to 15 are supported"
+        );
+        assert_eq!(
+            solve_error(r#"{"size":{"kinds":2,"values":6,"topology":{"Grid":4}},"rules":[]}"#),
+            "6 values do not fill rows of the grid"
+        );
+    }
+
+    #[test]
+    fn test_solve_rejects_rules_outside_puzzle() {
+        let error = solve_error(
+            r#"{"size":{"kinds":2,"values":6,"topology":"Line"},
+                "rules":[{"Open":[0,{"row":5,"value":0}]}]}"#,
+        );
+        assert!(error.contains("does not fit"), "{}", error);
+        let error = solve_error(
+            r#"{"size":{"kinds":2,"values":6,"topology":"Line"},
+                "rules":[{"NotAt":[6,{"row":1,"value":0}]}]}"#,
+        );
+        assert!(error.contains("does not fit"), "{}", error);
+    }
+
+    #[test]
+    fn test_solve_rejects_direction_at_round_table() {
+        let error = solve_error(
+            r#"{"size":{"kinds":2,"values":4,"topology":"Ring"},
+                "rules":[{"Direction":[{"row":0,"value":0},{"row":1,"value":0}]}]}"#,
+        );
+        assert!(error.contains("does not fit"), "{}", error);
+    }
+
+    #[test]
+    fn test_solve_generated_json() {
+        let args = Args::parse_from([
+            "einstein-cli",
+            "--kinds",
+            "4",
+            "--values",
+            "5",
+            "--count",
+            "2",
+        ]);
+        let generated = generate(&args).unwrap();
+        let json = serde_json::to_string(&generated).unwrap();
+
+        let PuzzlesJson::Many(puzzles) = serde_json::from_str(&json).unwrap() else {
+            panic!("Array of puzzles is expected");
+        };
+        for (puzzle, expected) in puzzles.into_iter().zip(generated) {
+            let solved = solve(PuzzleJson {
+                solution: None,
+                necessity: None,
+                ..puzzle
+            })
+            .unwrap();
+            assert_eq!(solved.solution, expected.solution);
+        }
+    }
+}
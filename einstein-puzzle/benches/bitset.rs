@@ -0,0 +1,22 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use einstein_puzzle::bitset::BitSet;
+
+fn bench_iter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BitSet::iter");
+    let sparse = BitSet::empty()
+        .with(3_u8.try_into().unwrap())
+        .with(11_u8.try_into().unwrap());
+    for (name, set) in [
+        ("empty", BitSet::empty()),
+        ("sparse", sparse),
+        ("full", BitSet::full(16)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| black_box(set).iter().map(usize::from).sum::<usize>())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_iter);
+criterion_main!(benches);
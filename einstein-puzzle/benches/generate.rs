@@ -0,0 +1,27 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::puzzle_gen::generate_puzzle;
+use einstein_puzzle::rules::{PuzzleSize, Topology};
+
+const SEED: u64 = 1;
+
+fn bench_generate(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generate_puzzle");
+    group.sample_size(10);
+    for n in 4..=10 {
+        let size = PuzzleSize {
+            kinds: n,
+            values: n,
+            topology: Topology::Line,
+        };
+        group.bench_with_input(
+            BenchmarkId::from_parameter(format!("{n}x{n}")),
+            &size,
+            |b, size| b.iter(|| generate_puzzle(*size, Difficulty::Medium, SEED).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_generate);
+criterion_main!(benches);
@@ -0,0 +1,95 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::propagation::propagate;
+use einstein_puzzle::puzzle_gen::generate_puzzle;
+use einstein_puzzle::rules::{
+    apply, Clause, Junction, Kind, Possibilities, PuzzleSize, Rule, SolvedPuzzle, Thing, Topology,
+};
+
+const SEED: u64 = 1;
+
+// A half solved board, so that rules have something to eliminate.
+fn fixture(size: PuzzleSize) -> (SolvedPuzzle, Possibilities) {
+    let (puzzle, rules) = generate_puzzle(size, Difficulty::Medium, SEED).unwrap();
+    let possibilities = propagate(&Possibilities::new(size), &rules[..rules.len() / 2]);
+    (puzzle, possibilities)
+}
+
+fn rules_for(puzzle: &SolvedPuzzle) -> Vec<(&'static str, Rule)> {
+    let thing = |row: u8, col: u8| -> Thing { puzzle.get(Kind(row), col) };
+    vec![
+        ("Near", Rule::Near(thing(0, 1), thing(1, 2))),
+        ("Direction", Rule::Direction(thing(2, 0), thing(3, 4))),
+        ("Open", Rule::Open(3, thing(4, 3))),
+        ("Under", Rule::Under(thing(1, 2), thing(5, 2))),
+        (
+            "Between",
+            Rule::Between(thing(0, 1), thing(2, 2), thing(4, 3)),
+        ),
+        ("NotNear", Rule::NotNear(thing(0, 0), thing(1, 4))),
+        ("NotUnder", Rule::NotUnder(thing(2, 1), thing(3, 3))),
+        ("NotAt", Rule::NotAt(0, thing(5, 2))),
+        ("LeftOf", Rule::LeftOf(thing(3, 2), thing(4, 3))),
+        ("Distance", Rule::Distance(thing(0, 0), thing(5, 3), 3)),
+        (
+            "Either",
+            Rule::Either(
+                Clause::At(1, thing(1, 1)),
+                Clause::Near(thing(2, 0), thing(3, 5)),
+                Junction::Inclusive,
+            ),
+        ),
+    ]
+}
+
+fn grid_rules_for(puzzle: &SolvedPuzzle) -> Vec<(&'static str, Rule)> {
+    let thing = |row: u8, col: u8| -> Thing { puzzle.get(Kind(row), col) };
+    vec![
+        ("Above", Rule::Above(thing(0, 1), thing(1, 4))),
+        ("SameRow", Rule::SameRow(thing(2, 3), thing(3, 5))),
+    ]
+}
+
+fn bench_apply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply");
+    let line = fixture(PuzzleSize {
+        kinds: 6,
+        values: 6,
+        topology: Topology::Line,
+    });
+    let grid = fixture(PuzzleSize {
+        kinds: 6,
+        values: 6,
+        topology: Topology::Grid(3),
+    });
+    for ((puzzle, possibilities), rules) in [
+        (&line, rules_for(&line.0)),
+        (&grid, grid_rules_for(&grid.0)),
+    ] {
+        for (name, rule) in rules {
+            assert!(rule.is_satisfied_by(puzzle));
+            group.bench_function(name, |b| {
+                b.iter(|| apply(black_box(possibilities), black_box(&rule)))
+            });
+        }
+    }
+    group.finish();
+}
+
+fn bench_is_valid(c: &mut Criterion) {
+    let mut group = c.benchmark_group("is_valid");
+    for n in [6, 10] {
+        let (puzzle, possibilities) = fixture(PuzzleSize {
+            kinds: n,
+            values: n,
+            topology: Topology::Line,
+        });
+        group.bench_function(format!("{n}x{n}"), |b| {
+            b.iter(|| black_box(&possibilities).is_valid(black_box(&puzzle)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_apply, bench_is_valid);
+criterion_main!(benches);
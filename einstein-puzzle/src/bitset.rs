@@ -75,6 +75,16 @@ impl BitSet {
         w.remove(value);
         w
     }
+
+    #[inline]
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    #[inline]
+    pub fn union(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
 }
 
 #[inline]
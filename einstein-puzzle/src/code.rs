@@ -0,0 +1,310 @@
+use crate::difficulty::Difficulty;
+use crate::puzzle_gen::{
+    generate_puzzle, generate_puzzle_with_control, puzzle_rng, GenerationControl, GenerationError,
+    GENERATOR_VERSION,
+};
+use crate::rules::{PuzzleSize, Rule, SolvedPuzzle, Topology};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+// Crockford's base32 alphabet: no I, L, O or U, so a code survives being read aloud.
+const ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+const CODE_LEN: usize = 16;
+const GROUP_LEN: usize = 4;
+
+// Grid puzzles carry their width in one more leading digit.
+const GRID_CODE_LEN: usize = CODE_LEN + 1;
+const WIDTH_BITS: u32 = 5;
+// The ring bit takes the place of the highest version bit, which was always zero.
+const RING_BITS: u32 = 1;
+const VERSION_BITS: u32 = 5;
+const KINDS_BITS: u32 = 4;
+const VALUES_BITS: u32 = 4;
+const DIFFICULTY_BITS: u32 = 2;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct PuzzleCode {
+    pub version: u8,
+    pub size: PuzzleSize,
+    pub difficulty: Difficulty,
+    pub seed: u64,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum InvalidPuzzleCode {
+    Malformed,
+    UnsupportedVersion(u8),
+    InvalidSize(PuzzleSize),
+}
+
+impl std::error::Error for InvalidPuzzleCode {}
+
+impl fmt::Display for InvalidPuzzleCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidPuzzleCode::Malformed => write!(f, "Malformed puzzle code"),
+            InvalidPuzzleCode::UnsupportedVersion(version) => {
+                write!(
+                    f,
+                    "Puzzle code of unsupported generator version {}",
+                    version
+                )
+            }
+            InvalidPuzzleCode::InvalidSize(size) => {
+                write!(f, "Invalid puzzle size {}x{}", size.kinds, size.values)
+            }
+        }
+    }
+}
+
+// Sizes which fit the bits of a code.
+fn check_size(size: PuzzleSize) -> Result<(), InvalidPuzzleCode> {
+    let fits = |count: u8, bits: u32| u32::from(count) < 1 << bits;
+    let width_fits = match size.topology {
+        Topology::Grid(width) => fits(width, WIDTH_BITS),
+        _ => true,
+    };
+    if size.is_valid()
+        && fits(size.kinds, KINDS_BITS)
+        && fits(size.values, VALUES_BITS)
+        && width_fits
+    {
+        Ok(())
+    } else {
+        Err(InvalidPuzzleCode::InvalidSize(size))
+    }
+}
+
+impl PuzzleCode {
+    pub fn new(
+        size: PuzzleSize,
+        difficulty: Difficulty,
+        seed: u64,
+    ) -> Result<Self, InvalidPuzzleCode> {
+        check_size(size)?;
+        Ok(Self {
+            version: GENERATOR_VERSION,
+            size,
+            difficulty,
+            seed,
+        })
+    }
+
+    pub fn random(
+        size: PuzzleSize,
+        difficulty: Difficulty,
+        rng: &mut impl Rng,
+    ) -> Result<Self, InvalidPuzzleCode> {
+        Self::new(size, difficulty, rng.gen())
+    }
+
+    pub fn generate(&self) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError> {
+        generate_puzzle(self.size, self.difficulty, self.seed)
+    }
+
+    pub fn generate_with(
+        &self,
+        control: &mut GenerationControl,
+    ) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError> {
+        generate_puzzle_with_control(
+            self.size,
+            self.difficulty,
+            &mut puzzle_rng(self.seed),
+            control,
+        )
+    }
+
+    fn len(&self) -> usize {
+        match self.size.topology {
+            Topology::Grid(_) => GRID_CODE_LEN,
+            _ => CODE_LEN,
+        }
+    }
+
+    fn pack(&self) -> u128 {
+        let (width, ring) = match self.size.topology {
+            Topology::Line => (0, 0),
+            Topology::Ring => (0, 1),
+            Topology::Grid(width) => (width, 0),
+        };
+        let mut bits = width as u128;
+        bits = (bits << RING_BITS) | ring;
+        bits = (bits << VERSION_BITS) | self.version as u128;
+        bits = (bits << KINDS_BITS) | self.size.kinds as u128;
+        bits = (bits << VALUES_BITS) | self.size.values as u128;
+        bits = (bits << DIFFICULTY_BITS) | self.difficulty as u128;
+        (bits << u64::BITS) | self.seed as u128
+    }
+
+    fn unpack(bits: u128) -> Self {
+        let take = |bits: u128, count: u32| (bits & ((1 << count) - 1)) as u8;
+        let seed = bits as u64;
+        let bits = bits >> u64::BITS;
+        let difficulty = Difficulty::ALL[take(bits, DIFFICULTY_BITS) as usize];
+        let bits = bits >> DIFFICULTY_BITS;
+        let values = take(bits, VALUES_BITS);
+        let bits = bits >> VALUES_BITS;
+        let kinds = take(bits, KINDS_BITS);
+        let bits = bits >> KINDS_BITS;
+        let version = take(bits, VERSION_BITS);
+        let bits = bits >> VERSION_BITS;
+        let ring = take(bits, RING_BITS);
+        let bits = bits >> RING_BITS;
+        let topology = match take(bits, WIDTH_BITS) {
+            0 if ring == 0 => Topology::Line,
+            0 => Topology::Ring,
+            width => Topology::Grid(width),
+        };
+        Self {
+            version,
+            size: PuzzleSize {
+                kinds,
+                values,
+                topology,
+            },
+            difficulty,
+            seed,
+        }
+    }
+}
+
+impl fmt::Display for PuzzleCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let bits = self.pack();
+        let len = self.len();
+        for i in 0..len {
+            if i > 0 && (len - i).is_multiple_of(GROUP_LEN) {
+                write!(f, "-")?;
+            }
+            let digit = (bits >> (5 * (len - 1 - i))) & 0x1f;
+            write!(f, "{}", ALPHABET[digit as usize] as char)?;
+        }
+        Ok(())
+    }
+}
+
+fn decode_digit(c: char) -> Option<u128> {
+    let c = match c.to_ascii_uppercase() {
+        'O' => '0',
+        'I' | 'L' => '1',
+        c => c,
+    };
+    ALPHABET
+        .iter()
+        .position(|a| *a as char == c)
+        .map(|digit| digit as u128)
+}
+
+impl FromStr for PuzzleCode {
+    type Err = InvalidPuzzleCode;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut bits: u128 = 0;
+        let mut len = 0;
+        for c in s.chars().filter(|c| *c != '-' && !c.is_whitespace()) {
+            let digit = decode_digit(c).ok_or(InvalidPuzzleCode::Malformed)?;
+            bits = (bits << 5) | digit;
+            len += 1;
+            if len > GRID_CODE_LEN {
+                return Err(InvalidPuzzleCode::Malformed);
+            }
+        }
+
+        let code = Self::unpack(bits);
+        if code.len() != len || code.pack() != bits {
+            return Err(InvalidPuzzleCode::Malformed);
+        }
+        if code.version != GENERATOR_VERSION {
+            return Err(InvalidPuzzleCode::UnsupportedVersion(code.version));
+        }
+        check_size(code.size)?;
+        Ok(code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::thread_rng;
+
+    #[test]
+    fn test_code_roundtrip() {
+        let mut rng = thread_rng();
+        for difficulty in Difficulty::ALL {
+            let code = PuzzleCode::random(PuzzleSize::default(), difficulty, &mut rng).unwrap();
+            let text = code.to_string();
+            assert_eq!(text.len(), 19);
+            assert_eq!(text.parse(), Ok(code));
+            assert_eq!(text.to_lowercase().replace('-', "").parse(), Ok(code));
+        }
+
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 7,
+            topology: Topology::Ring,
+        };
+        let code = PuzzleCode::random(size, Difficulty::Hard, &mut rng).unwrap();
+        assert_eq!(code.to_string().parse(), Ok(code));
+
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 6,
+            topology: Topology::Grid(3),
+        };
+        let code = PuzzleCode::random(size, Difficulty::Easy, &mut rng).unwrap();
+        let text = code.to_string();
+        assert_eq!(text.len(), 21);
+        assert!(text.starts_with("3-"));
+        assert_eq!(text.parse(), Ok(code));
+    }
+
+    #[test]
+    fn test_largest_code_roundtrip() {
+        let size = PuzzleSize {
+            kinds: 15,
+            values: 15,
+            topology: Topology::Line,
+        };
+        let code = PuzzleCode::new(size, Difficulty::Expert, u64::MAX).unwrap();
+        assert_eq!(code.to_string().parse(), Ok(code));
+
+        let size = PuzzleSize {
+            kinds: 15,
+            values: 15,
+            topology: Topology::Grid(15),
+        };
+        let code = PuzzleCode::new(size, Difficulty::Expert, u64::MAX).unwrap();
+        assert_eq!(code.to_string().parse(), Ok(code));
+
+        for (kinds, values) in [(16, 16), (16, 4), (4, 16)] {
+            let size = PuzzleSize {
+                kinds,
+                values,
+                topology: Topology::Line,
+            };
+            assert_eq!(
+                PuzzleCode::new(size, Difficulty::Medium, 1),
+                Err(InvalidPuzzleCode::InvalidSize(size))
+            );
+        }
+    }
+
+    #[test]
+    fn test_malformed_code() {
+        assert_eq!("".parse::<PuzzleCode>(), Err(InvalidPuzzleCode::Malformed));
+        assert_eq!(
+            "1234-5678-9ABC-DEFU".parse::<PuzzleCode>(),
+            Err(InvalidPuzzleCode::Malformed)
+        );
+        assert_eq!(
+            "0000-0000-0000-0000".parse::<PuzzleCode>(),
+            Err(InvalidPuzzleCode::UnsupportedVersion(0))
+        );
+        assert_eq!(
+            "0-0000-0000-0000-0000".parse::<PuzzleCode>(),
+            Err(InvalidPuzzleCode::Malformed)
+        );
+    }
+}
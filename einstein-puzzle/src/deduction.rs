@@ -0,0 +1,127 @@
+use crate::rules::{apply_traced, Elimination, Possibilities, PuzzleSize, Rule};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Deduction {
+    pub rule: Rule,
+    pub eliminations: Vec<Elimination>,
+}
+
+impl fmt::Display for Deduction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}:", self.rule)?;
+        for elimination in &self.eliminations {
+            writeln!(f, "  {}", elimination.change)?;
+            for propagation in &elimination.propagations {
+                writeln!(f, "    => {}", propagation)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Explanation {
+    pub possibilities: Possibilities,
+    pub deductions: Vec<Deduction>,
+}
+
+pub fn next_deduction(possibilities: &Possibilities, rules: &[Rule]) -> Option<Deduction> {
+    rules.iter().find_map(|rule| {
+        let (_, eliminations) = apply_traced(possibilities, rule);
+        if eliminations.is_empty() {
+            None
+        } else {
+            Some(Deduction {
+                rule: *rule,
+                eliminations,
+            })
+        }
+    })
+}
+
+pub fn explain_from(possibilities: &Possibilities, rules: &[Rule]) -> Explanation {
+    let mut possibilities = possibilities.clone();
+    let mut deductions = Vec::new();
+    loop {
+        let mut changed = false;
+        for rule in rules {
+            let (next, eliminations) = apply_traced(&possibilities, rule);
+            if !eliminations.is_empty() {
+                changed = true;
+                deductions.push(Deduction {
+                    rule: *rule,
+                    eliminations,
+                });
+            }
+            possibilities = next;
+        }
+        if !changed {
+            return Explanation {
+                possibilities,
+                deductions,
+            };
+        }
+    }
+}
+
+pub fn explain(size: PuzzleSize, rules: &[Rule]) -> Explanation {
+    explain_from(&Possibilities::new(size), rules)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::puzzle_gen::generate_puzzle_with_rng;
+    use crate::rules::{apply, Change, Kind, Thing, Topology, Value};
+    use rand::thread_rng;
+
+    #[test]
+    fn test_explain_reaches_solution() {
+        let size = PuzzleSize {
+            kinds: 6,
+            values: 6,
+            topology: Topology::Line,
+        };
+        let (puzzle, rules) =
+            generate_puzzle_with_rng(size, Difficulty::Medium, &mut thread_rng()).unwrap();
+        let explanation = explain(size, &rules);
+        assert!(explanation.possibilities.is_solved());
+        assert!(explanation.possibilities.is_valid(&puzzle));
+
+        let mut possibilities = Possibilities::new(size);
+        for deduction in &explanation.deductions {
+            possibilities = apply(&possibilities, &deduction.rule);
+        }
+        assert_eq!(possibilities, explanation.possibilities);
+    }
+
+    #[test]
+    fn test_open_rule_propagates() {
+        let size = PuzzleSize {
+            kinds: 1,
+            values: 2,
+            topology: Topology::Line,
+        };
+        let thing = Thing {
+            row: Kind(0),
+            value: Value(1),
+        };
+        let deduction = next_deduction(&Possibilities::new(size), &[Rule::Open(0, thing)]).unwrap();
+        assert_eq!(
+            deduction.eliminations,
+            vec![Elimination {
+                change: Change::Set(0, thing),
+                propagations: vec![Change::Set(
+                    1,
+                    Thing {
+                        row: Kind(0),
+                        value: Value(0),
+                    }
+                )],
+            }]
+        );
+    }
+}
@@ -0,0 +1,219 @@
+use crate::propagation::propagate;
+use crate::rules::{apply, arc_consistency, Possibilities, Propagation, PuzzleSize, Rule};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+const EASY_MAX_ROUNDS: usize = 6;
+const HARD_MAX_CASE_ANALYSES: usize = 3;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Medium,
+    Hard,
+    Expert,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+    ];
+
+    pub fn needs_case_analysis(self) -> bool {
+        matches!(self, Difficulty::Hard | Difficulty::Expert)
+    }
+}
+
+impl fmt::Display for Difficulty {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Difficulty::Easy => write!(f, "easy"),
+            Difficulty::Medium => write!(f, "medium"),
+            Difficulty::Hard => write!(f, "hard"),
+            Difficulty::Expert => write!(f, "expert"),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub struct Rating {
+    pub rounds: usize,
+    pub case_analyses: usize,
+    /// The weakest propagation which solves the puzzle without case analysis.
+    pub propagation: Option<Propagation>,
+}
+
+impl Rating {
+    pub fn difficulty(&self) -> Difficulty {
+        if self.case_analyses == 0 {
+            if self.rounds <= EASY_MAX_ROUNDS {
+                Difficulty::Easy
+            } else {
+                Difficulty::Medium
+            }
+        } else if self.case_analyses <= HARD_MAX_CASE_ANALYSES {
+            Difficulty::Hard
+        } else {
+            Difficulty::Expert
+        }
+    }
+}
+
+fn cross_rows(possibilities: &Possibilities, rules: &[Rule]) -> Possibilities {
+    if possibilities.propagation() >= Propagation::ArcConsistency {
+        arc_consistency(possibilities, rules)
+    } else {
+        possibilities.clone()
+    }
+}
+
+fn propagation_round(possibilities: &Possibilities, rules: &[Rule]) -> Possibilities {
+    rules
+        .iter()
+        .fold(cross_rows(possibilities, rules), |acc, rule| {
+            acc.intersect(&apply(possibilities, rule))
+        })
+}
+
+fn eliminate_by_contradiction(
+    possibilities: &Possibilities,
+    rules: &[Rule],
+) -> Option<Possibilities> {
+    let size = possibilities.size();
+    for kind in size.iter_kinds() {
+        for col in 0..size.values {
+            if possibilities.is_defined(col, kind) {
+                continue;
+            }
+            for value in possibilities.get_possible(col, kind).iter() {
+                let trial = propagate(&possibilities.set(col, kind, value), rules);
+                if !trial.is_consistent() {
+                    return Some(possibilities.exclude(col, kind, value));
+                }
+            }
+        }
+    }
+    None
+}
+
+pub(crate) fn solve_with_case_analysis(
+    possibilities: &Possibilities,
+    rules: &[Rule],
+) -> (Possibilities, usize) {
+    let mut possibilities = propagate(possibilities, rules);
+    let mut case_analyses = 0;
+    while !possibilities.is_solved() && possibilities.is_consistent() {
+        match eliminate_by_contradiction(&possibilities, rules) {
+            Some(next) => possibilities = propagate(&next, rules),
+            None => break,
+        }
+        case_analyses += 1;
+    }
+    (possibilities, case_analyses)
+}
+
+pub fn rate_from(possibilities: &Possibilities, rules: &[Rule]) -> Option<Rating> {
+    let mut possibilities = possibilities.clone();
+    let mut rating = Rating::default();
+    loop {
+        loop {
+            let next = propagation_round(&possibilities, rules);
+            if next == possibilities {
+                break;
+            }
+            possibilities = next;
+            rating.rounds += 1;
+        }
+        if possibilities.is_solved() {
+            return Some(rating);
+        }
+        if !possibilities.is_consistent() {
+            return None;
+        }
+        possibilities = eliminate_by_contradiction(&possibilities, rules)?;
+        rating.case_analyses += 1;
+    }
+}
+
+pub fn rate(size: PuzzleSize, rules: &[Rule]) -> Option<Rating> {
+    let rating = rate_from(&Possibilities::new(size), rules)?;
+    Some(Rating {
+        propagation: required_propagation(size, rules),
+        ..rating
+    })
+}
+
+/// The weakest propagation which solves the rules without case analysis.
+pub fn required_propagation(size: PuzzleSize, rules: &[Rule]) -> Option<Propagation> {
+    Propagation::ALL.into_iter().find(|propagation| {
+        let possibilities = Possibilities::new(size).with_propagation(*propagation);
+        propagate(&possibilities, rules).is_solved()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle_gen::generate_puzzle;
+    use crate::rules::Topology;
+
+    #[test]
+    fn test_generated_puzzle_has_requested_difficulty() {
+        let size = PuzzleSize {
+            kinds: 5,
+            values: 5,
+            topology: Topology::Line,
+        };
+        for difficulty in Difficulty::ALL {
+            let (_puzzle, rules) = generate_puzzle(size, difficulty, 100).unwrap();
+            assert_eq!(rate(size, &rules).unwrap().difficulty(), difficulty);
+        }
+    }
+
+    #[test]
+    fn test_case_analysis_is_required_for_hard() {
+        let size = PuzzleSize {
+            kinds: 5,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let (puzzle, rules) = generate_puzzle(size, Difficulty::Hard, 1).unwrap();
+        assert!(!propagate(&Possibilities::new(size), &rules).is_solved());
+        let (possibilities, case_analyses) =
+            solve_with_case_analysis(&Possibilities::new(size), &rules);
+        assert!(possibilities.is_solved());
+        assert!(possibilities.is_valid(&puzzle));
+        assert!(case_analyses > 0);
+        let rating = rate(size, &rules).unwrap();
+        assert_eq!(rating.difficulty(), Difficulty::Hard);
+        assert_ne!(rating.propagation, Some(Propagation::Singles));
+    }
+
+    #[test]
+    fn test_required_propagation() {
+        let size = PuzzleSize {
+            kinds: 5,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let (puzzle, rules) = generate_puzzle(size, Difficulty::Medium, 1).unwrap();
+        assert_eq!(
+            required_propagation(size, &rules),
+            Some(Propagation::Singles)
+        );
+        assert_eq!(
+            rate(size, &rules).unwrap().propagation,
+            Some(Propagation::Singles)
+        );
+        for propagation in Propagation::ALL {
+            let possibilities = Possibilities::new(size).with_propagation(propagation);
+            let solved = propagate(&possibilities, &rules);
+            assert!(solved.is_solved());
+            assert!(solved.is_valid(&puzzle));
+        }
+    }
+}
@@ -1,6 +1,12 @@
 pub mod bitset;
+pub mod code;
+pub mod deduction;
+pub mod difficulty;
+pub mod minimize;
+pub mod propagation;
 pub mod puzzle;
 pub mod puzzle_gen;
 pub mod rules;
+pub mod solver;
 pub mod u4;
 pub mod util;
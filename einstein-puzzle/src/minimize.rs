@@ -0,0 +1,319 @@
+use crate::propagation::propagate;
+use crate::puzzle_gen::{GenerationError, Limits};
+use crate::rules::{Possibilities, Propagation, PuzzleSize, Rule};
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Whether propagation of the given strength alone solves the puzzle.
+pub fn is_solved_by(size: PuzzleSize, rules: &[Rule], propagation: Propagation) -> bool {
+    propagate(
+        &Possibilities::new(size).with_propagation(propagation),
+        rules,
+    )
+    .is_solved()
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Necessity {
+    /// The other rules do not solve the puzzle without it.
+    Necessary,
+    /// The other rules solve the puzzle on their own.
+    Redundant,
+}
+
+/// Tells for every rule whether the puzzle can do without it.
+pub fn necessity(rules: &[Rule], solves: impl Fn(&[Rule]) -> bool + Sync) -> Vec<Necessity> {
+    (0..rules.len())
+        .into_par_iter()
+        .map(|index| {
+            let mut others = rules.to_vec();
+            others.remove(index);
+            if solves(&others) {
+                Necessity::Redundant
+            } else {
+                Necessity::Necessary
+            }
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug)]
+pub struct Minimized {
+    pub rules: Vec<Rule>,
+    /// The search ran to the end, so no smaller subset solves the puzzle.
+    pub proven: bool,
+    /// Nodes of the search tree visited.
+    pub nodes: usize,
+}
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Choice {
+    Undecided,
+    Included,
+    Excluded,
+}
+
+struct Search<'a, F> {
+    rules: &'a [Rule],
+    solves: F,
+    limits: Limits<'a>,
+    nodes_left: usize,
+    nodes: usize,
+    exhausted: bool,
+    stopped: Option<GenerationError>,
+    best: Vec<usize>,
+}
+
+impl<F> Search<'_, F>
+where
+    F: Fn(&[Rule]) -> bool + Sync,
+{
+    fn subset(&self, choices: &[Choice], keep: impl Fn(Choice) -> bool) -> Vec<usize> {
+        (0..self.rules.len())
+            .filter(|&index| keep(choices[index]))
+            .collect()
+    }
+
+    fn solves(&self, indices: &[usize]) -> bool {
+        let rules: Vec<Rule> = indices.iter().map(|&index| self.rules[index]).collect();
+        (self.solves)(&rules)
+    }
+
+    // Branch and bound over including or excluding every rule in turn.
+    // Excluding first finds small solving subsets early, which then bound
+    // the rest of the search.
+    fn stop(&mut self) -> bool {
+        if self.stopped.is_none() {
+            self.stopped = self.limits.check().err();
+        }
+        self.stopped.is_some()
+    }
+
+    fn search(&mut self, choices: &[Choice]) {
+        if self.stop() {
+            return;
+        }
+        if self.nodes_left == 0 {
+            self.exhausted = true;
+            return;
+        }
+        self.nodes_left -= 1;
+        self.nodes += 1;
+
+        let available = self.subset(choices, |choice| choice != Choice::Excluded);
+        if !self.solves(&available) {
+            return;
+        }
+
+        // undecided rules nothing else can replace
+        let undecided = self.subset(choices, |choice| choice == Choice::Undecided);
+        let limits = self.limits;
+        let forced: Vec<usize> = undecided
+            .par_iter()
+            .copied()
+            .filter(|&index| {
+                if limits.check().is_err() {
+                    return false;
+                }
+                let others: Vec<usize> = available
+                    .iter()
+                    .copied()
+                    .filter(|&other| other != index)
+                    .collect();
+                !self.solves(&others)
+            })
+            .collect();
+        if self.stop() {
+            return;
+        }
+        let mut choices = choices.to_vec();
+        for &index in &forced {
+            choices[index] = Choice::Included;
+        }
+
+        let included = self.subset(&choices, |choice| choice == Choice::Included);
+        if included.len() >= self.best.len() {
+            return;
+        }
+        if self.solves(&included) {
+            self.best = included;
+            return;
+        }
+        // one more rule is needed at least
+        if included.len() + 1 >= self.best.len() {
+            return;
+        }
+
+        let Some(next) = choices
+            .iter()
+            .position(|&choice| choice == Choice::Undecided)
+        else {
+            return;
+        };
+        for choice in [Choice::Excluded, Choice::Included] {
+            choices[next] = choice;
+            self.search(&choices);
+        }
+    }
+}
+
+/// Looks for a smallest subset of the rules that still `solves` the puzzle,
+/// visiting at most `max_nodes` nodes of the search tree. When the budget
+/// runs out the smallest subset found so far is returned.
+///
+/// `solves` is assumed to be monotonic: more rules never make a solvable
+/// puzzle unsolvable.
+pub fn minimize(
+    rules: &[Rule],
+    max_nodes: usize,
+    solves: impl Fn(&[Rule]) -> bool + Sync,
+) -> Minimized {
+    minimize_within(rules, max_nodes, Limits::default(), solves)
+        .expect("Search without limits is never stopped")
+}
+
+/// Same as `minimize`, but gives up when `limits` say so.
+pub(crate) fn minimize_within(
+    rules: &[Rule],
+    max_nodes: usize,
+    limits: Limits,
+    solves: impl Fn(&[Rule]) -> bool + Sync,
+) -> Result<Minimized, GenerationError> {
+    let mut search = Search {
+        rules,
+        solves,
+        limits,
+        nodes_left: max_nodes,
+        nodes: 0,
+        exhausted: false,
+        stopped: None,
+        best: (0..rules.len()).collect(),
+    };
+    search.search(&vec![Choice::Undecided; rules.len()]);
+    if let Some(error) = search.stopped {
+        return Err(error);
+    }
+    Ok(Minimized {
+        rules: search.best.iter().map(|&index| rules[index]).collect(),
+        proven: !search.exhausted,
+        nodes: search.nodes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzle_gen::puzzle_rng;
+    use crate::rules::{generate_rule, SolvedPuzzle, Topology};
+    use crate::util::retry::retry;
+    use std::sync::atomic::AtomicBool;
+
+    fn size() -> PuzzleSize {
+        PuzzleSize {
+            kinds: 3,
+            values: 4,
+            topology: Topology::Line,
+        }
+    }
+
+    // Random true rules until propagation solves the puzzle.
+    fn solving_rules(seed: u64) -> Vec<Rule> {
+        let mut rng = puzzle_rng(seed);
+        let puzzle = SolvedPuzzle::random(size(), &mut rng);
+        let mut rules = Vec::new();
+        while !is_solved_by(size(), &rules, Propagation::Singles) {
+            let rule = retry(
+                || generate_rule(&mut rng, &puzzle),
+                |rule| !rules.contains(rule),
+            );
+            rules.push(rule);
+        }
+        rules
+    }
+
+    // Smallest solving subset by trying all of them.
+    fn brute_force(rules: &[Rule], propagation: Propagation) -> usize {
+        (0_u32..1 << rules.len())
+            .filter(|mask| {
+                let subset: Vec<Rule> = (0..rules.len())
+                    .filter(|index| mask & (1 << index) != 0)
+                    .map(|index| rules[index])
+                    .collect();
+                is_solved_by(size(), &subset, propagation)
+            })
+            .map(|mask| mask.count_ones() as usize)
+            .min()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_minimize_finds_smallest_subset() {
+        for seed in 0..6 {
+            let rules = solving_rules(seed);
+            if rules.len() > 14 {
+                continue;
+            }
+            for propagation in [Propagation::Singles, Propagation::ArcConsistency] {
+                let minimized = minimize(&rules, usize::MAX, |rules| {
+                    is_solved_by(size(), rules, propagation)
+                });
+                assert!(minimized.proven);
+                assert!(is_solved_by(size(), &minimized.rules, propagation));
+                assert_eq!(minimized.rules.len(), brute_force(&rules, propagation));
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimize_within_budget() {
+        let rules = solving_rules(1);
+        let minimized = minimize(&rules, 1, |rules| {
+            is_solved_by(size(), rules, Propagation::Singles)
+        });
+        assert!(!minimized.proven);
+        assert!(is_solved_by(size(), &minimized.rules, Propagation::Singles));
+    }
+
+    #[test]
+    fn test_minimize_proven_with_exact_budget() {
+        let rules = solving_rules(1);
+        let solves = |rules: &[Rule]| is_solved_by(size(), rules, Propagation::Singles);
+        let unlimited = minimize(&rules, usize::MAX, solves);
+        assert!(unlimited.proven);
+        let exact = minimize(&rules, unlimited.nodes, solves);
+        assert!(exact.proven);
+        assert_eq!(exact.rules, unlimited.rules);
+        assert!(!minimize(&rules, unlimited.nodes - 1, solves).proven);
+    }
+
+    #[test]
+    fn test_minimize_stops_when_cancelled() {
+        let rules = solving_rules(1);
+        let cancel = AtomicBool::new(true);
+        let limits = Limits {
+            cancel: Some(&cancel),
+            ..Limits::default()
+        };
+        let result = minimize_within(&rules, usize::MAX, limits, |rules| {
+            is_solved_by(size(), rules, Propagation::Singles)
+        });
+        assert!(matches!(result, Err(GenerationError::Cancelled)));
+    }
+
+    #[test]
+    fn test_necessity() {
+        let rules = solving_rules(2);
+        let solves = |rules: &[Rule]| is_solved_by(size(), rules, Propagation::Singles);
+        let minimized = minimize(&rules, usize::MAX, solves);
+        assert!(necessity(&minimized.rules, solves)
+            .iter()
+            .all(|necessity| *necessity == Necessity::Necessary));
+
+        let mut padded = minimized.rules.clone();
+        padded.push(rules[0]);
+        padded.push(rules[0]);
+        let report = necessity(&padded, solves);
+        assert_eq!(report[padded.len() - 1], Necessity::Redundant);
+        assert_eq!(report[padded.len() - 2], Necessity::Redundant);
+    }
+}
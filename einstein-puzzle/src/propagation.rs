@@ -0,0 +1,110 @@
+use crate::rules::{apply, revise, Possibilities, Propagation, Rule};
+use std::collections::VecDeque;
+use std::convert::Infallible;
+
+/// Applies the rules until nothing changes, like running them all over and
+/// over, but only re-runs a rule when a row it mentions has changed.
+pub fn propagate(possibilities: &Possibilities, rules: &[Rule]) -> Possibilities {
+    match propagate_checked(possibilities, rules, |_, _| Ok::<(), Infallible>(())) {
+        Ok(possibilities) => possibilities,
+        Err(never) => match never {},
+    }
+}
+
+/// Same as `propagate`, but lets `check` stop propagation after any rule.
+pub fn propagate_checked<E>(
+    possibilities: &Possibilities,
+    rules: &[Rule],
+    mut check: impl FnMut(&Possibilities, &Rule) -> Result<(), E>,
+) -> Result<Possibilities, E> {
+    let size = possibilities.size();
+    let arc = possibilities.propagation() >= Propagation::ArcConsistency;
+
+    // rules to re-run when a row of the kind changes
+    let mut watchers: Vec<Vec<usize>> = vec![Vec::new(); usize::from(size.kinds)];
+    for (index, rule) in rules.iter().enumerate() {
+        for thing in rule.things() {
+            let kind_watchers = &mut watchers[usize::from(thing.row.0)];
+            if !kind_watchers.contains(&index) {
+                kind_watchers.push(index);
+            }
+        }
+    }
+
+    let mut queue: VecDeque<usize> = (0..rules.len()).collect();
+    let mut queued = vec![true; rules.len()];
+    let mut possibilities = possibilities.clone();
+    while let Some(index) = queue.pop_front() {
+        queued[index] = false;
+        let rule = &rules[index];
+        let mut next = apply(&possibilities, rule);
+        if arc {
+            next = revise(&next, *rule);
+        }
+        check(&next, rule)?;
+        for kind in possibilities.changed_kinds(&next) {
+            for watcher in &watchers[usize::from(kind.0)] {
+                if !queued[*watcher] {
+                    queued[*watcher] = true;
+                    queue.push_back(*watcher);
+                }
+            }
+        }
+        possibilities = next;
+        if !possibilities.is_consistent() {
+            // nothing sensible can be deduced from a contradiction
+            break;
+        }
+    }
+    Ok(possibilities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::puzzle_gen::generate_puzzle;
+    use crate::rules::{PuzzleSize, Topology};
+    use crate::util::converge::converge;
+
+    fn propagate_naively(possibilities: &Possibilities, rules: &[Rule]) -> Possibilities {
+        let arc = possibilities.propagation() >= Propagation::ArcConsistency;
+        converge(possibilities.clone(), |possibilities| {
+            rules.iter().fold(possibilities, |possibilities, rule| {
+                let next = apply(&possibilities, rule);
+                if arc {
+                    revise(&next, *rule)
+                } else {
+                    next
+                }
+            })
+        })
+    }
+
+    #[test]
+    fn test_same_result_as_running_every_rule() {
+        let size = PuzzleSize {
+            kinds: 5,
+            values: 5,
+            topology: Topology::Line,
+        };
+        for seed in 0..4 {
+            let (puzzle, rules) = generate_puzzle(size, Difficulty::Medium, seed).unwrap();
+            // drop some rules to stop half way
+            for skip in [1, 3, 4] {
+                let rules: Vec<Rule> = rules.iter().step_by(skip).copied().collect();
+                for propagation in Propagation::ALL {
+                    let start = Possibilities::new(size).with_propagation(propagation);
+                    let possibilities = propagate(&start, &rules);
+                    assert_eq!(
+                        possibilities,
+                        propagate_naively(&start, &rules),
+                        "{}",
+                        propagation
+                    );
+                    assert!(possibilities.is_valid(&puzzle));
+                }
+            }
+        }
+    }
+}
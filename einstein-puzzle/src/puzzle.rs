@@ -1,6 +1,6 @@
-use crate::puzzle_gen::generate_puzzle;
+use crate::difficulty::Difficulty;
+use crate::puzzle_gen::{generate_puzzle, GenerationError};
 use crate::rules::{apply, Kind, Possibilities, PuzzleSize, Rule, SolvedPuzzle, Thing, Value};
-use rand::{thread_rng, Rng};
 use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -17,8 +17,12 @@ pub enum PuzzleState {
 }
 
 impl Puzzle {
-    pub fn new(size: PuzzleSize, rng: &mut impl Rng) -> Self {
-        let (solved_puzzle, rules) = generate_puzzle(size, rng).expect("Puzzle is generated");
+    pub fn new(
+        size: PuzzleSize,
+        difficulty: Difficulty,
+        seed: u64,
+    ) -> Result<Self, GenerationError> {
+        let (solved_puzzle, rules) = generate_puzzle(size, difficulty, seed)?;
 
         let mut possibilities = Possibilities::new(solved_puzzle.size());
         for rule in &rules {
@@ -27,11 +31,11 @@ impl Puzzle {
             }
         }
 
-        Self {
+        Ok(Self {
             solved_puzzle,
             rules,
             possibilities,
-        }
+        })
     }
 
     pub fn size(&self) -> PuzzleSize {
@@ -76,9 +80,3 @@ impl Puzzle {
         self.possibilities = possibilities;
     }
 }
-
-impl std::default::Default for Puzzle {
-    fn default() -> Self {
-        Self::new(PuzzleSize::default(), &mut thread_rng())
-    }
-}
@@ -1,6 +1,15 @@
+use crate::difficulty::{rate_from, solve_with_case_analysis, Difficulty, Rating};
+use crate::minimize::{minimize_within, necessity, Necessity};
+use crate::propagation::propagate_checked;
 use crate::rules::*;
-use crate::util::{converge::converge_result, retry::retry};
-use rand::Rng;
+use crate::util::retry::retry_limited;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use rayon::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+pub const GENERATOR_VERSION: u8 = 4;
 
 #[derive(Debug)]
 pub struct InvalidPuzzle(Rule);
@@ -13,69 +22,752 @@ impl std::fmt::Display for InvalidPuzzle {
     }
 }
 
-fn solve(puzzle: &SolvedPuzzle, rules: &[Rule]) -> Result<Possibilities, InvalidPuzzle> {
-    let possibilities = converge_result(Possibilities::new(puzzle.size()), |mut possibilities| {
-        for rule in rules {
-            possibilities = apply(&possibilities, rule);
-            if !possibilities.is_valid(puzzle) {
-                return Err(InvalidPuzzle(*rule));
+#[derive(Debug)]
+pub enum GenerationError {
+    InvalidPuzzle(InvalidPuzzle),
+    /// Every rule that holds for the solution is already used.
+    OutOfRules,
+    TimedOut,
+    Cancelled,
+    /// No attempt gave a puzzle of the requested difficulty.
+    DifficultyNotReached(Difficulty),
+    /// The required rules break the limit on `Open` givens.
+    TooManyOpenRules,
+}
+
+impl std::error::Error for GenerationError {}
+
+impl std::fmt::Display for GenerationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            GenerationError::InvalidPuzzle(invalid) => invalid.fmt(f),
+            GenerationError::OutOfRules => write!(f, "No more rules to add to the puzzle"),
+            GenerationError::TimedOut => write!(f, "Puzzle generation timed out"),
+            GenerationError::Cancelled => write!(f, "Puzzle generation cancelled"),
+            GenerationError::DifficultyNotReached(difficulty) => {
+                write!(f, "Cannot generate a puzzle of {} difficulty", difficulty)
+            }
+            GenerationError::TooManyOpenRules => {
+                write!(f, "Required rules open more columns than allowed")
             }
         }
-        Ok(possibilities)
-    })?;
-    Ok(possibilities)
+    }
+}
+
+impl From<InvalidPuzzle> for GenerationError {
+    fn from(invalid: InvalidPuzzle) -> Self {
+        GenerationError::InvalidPuzzle(invalid)
+    }
+}
+
+/// What the rules of a puzzle with a given solution have to look like.
+#[derive(Clone, Debug, Default)]
+pub struct RuleConstraints {
+    /// Rules the puzzle always has; they are never removed.
+    pub required: Vec<Rule>,
+    /// Kinds of rules never added to the required ones.
+    pub forbidden: Vec<RuleKind>,
+    /// At most this many `Open` rules, required ones included.
+    pub max_open: Option<usize>,
+    pub mix: RuleMix,
+}
+
+impl RuleConstraints {
+    fn open_count(rules: &[Rule]) -> usize {
+        rules
+            .iter()
+            .filter(|rule| rule.kind() == RuleKind::Open)
+            .count()
+    }
+
+    // The mix for the next rule after `rules`.
+    fn next_mix(&self, rules: &[Rule]) -> RuleMix {
+        let mut mix = self.mix;
+        for &kind in &self.forbidden {
+            mix = mix.with_weight(kind, 0);
+        }
+        if self
+            .max_open
+            .is_some_and(|max_open| Self::open_count(rules) >= max_open)
+        {
+            mix = mix.with_weight(RuleKind::Open, 0);
+        }
+        mix
+    }
+}
+
+/// How far the generator got with the current attempt.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub attempt: usize,
+    pub rules_added: usize,
+    pub rules_removed: usize,
+}
+
+// The part of the control that workers of one generation share.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Limits<'a> {
+    pub(crate) deadline: Option<Instant>,
+    pub(crate) cancel: Option<&'a AtomicBool>,
+}
+
+impl Limits<'_> {
+    pub(crate) fn check(&self) -> Result<(), GenerationError> {
+        if self
+            .cancel
+            .is_some_and(|cancel| cancel.load(Ordering::Relaxed))
+        {
+            Err(GenerationError::Cancelled)
+        } else if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            Err(GenerationError::TimedOut)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default)]
+pub enum Minimization {
+    /// Drops rules in order while the puzzle stays solvable.
+    #[default]
+    Greedy,
+    /// Searches for a smallest set of rules, visiting at most `max_nodes`
+    /// nodes of the search tree. The difficulty is judged for a board which
+    /// propagates with `propagation`.
+    Smallest {
+        max_nodes: usize,
+        propagation: Propagation,
+    },
+}
+
+impl Minimization {
+    fn propagation(self) -> Propagation {
+        match self {
+            Minimization::Greedy => Propagation::default(),
+            Minimization::Smallest { propagation, .. } => propagation,
+        }
+    }
+}
+
+/// Deadline, cancellation and progress reporting for a generation.
+#[derive(Default)]
+pub struct GenerationControl<'a> {
+    limits: Limits<'a>,
+    minimization: Minimization,
+    on_progress: Option<Box<dyn FnMut(Progress) + 'a>>,
+    progress: Progress,
+}
+
+impl<'a> GenerationControl<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_deadline(mut self, deadline: Instant) -> Self {
+        self.limits.deadline = Some(deadline);
+        self
+    }
+
+    pub fn with_timeout(self, timeout: Duration) -> Self {
+        self.with_deadline(Instant::now() + timeout)
+    }
+
+    /// Generation stops once `cancel` is set.
+    pub fn with_cancel(mut self, cancel: &'a AtomicBool) -> Self {
+        self.limits.cancel = Some(cancel);
+        self
+    }
+
+    pub fn with_minimization(mut self, minimization: Minimization) -> Self {
+        self.minimization = minimization;
+        self
+    }
+
+    pub fn with_progress(mut self, on_progress: impl FnMut(Progress) + 'a) -> Self {
+        self.on_progress = Some(Box::new(on_progress));
+        self
+    }
+
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    fn check(&self) -> Result<(), GenerationError> {
+        self.limits.check()
+    }
+
+    fn update(&mut self, update: impl FnOnce(&mut Progress)) {
+        update(&mut self.progress);
+        if let Some(on_progress) = &mut self.on_progress {
+            on_progress(self.progress);
+        }
+    }
+}
+
+fn solve(
+    puzzle: &SolvedPuzzle,
+    rules: &[Rule],
+    propagation: Propagation,
+) -> Result<Possibilities, InvalidPuzzle> {
+    propagate_checked(
+        &Possibilities::new(puzzle.size()).with_propagation(propagation),
+        rules,
+        |possibilities, rule| {
+            if possibilities.is_valid(puzzle) {
+                Ok(())
+            } else {
+                Err(InvalidPuzzle(*rule))
+            }
+        },
+    )
+}
+
+fn rate_with(size: PuzzleSize, rules: &[Rule], propagation: Propagation) -> Option<Difficulty> {
+    rate_from(
+        &Possibilities::new(size).with_propagation(propagation),
+        rules,
+    )
+    .map(|rating| rating.difficulty())
+}
+
+fn can_solve(
+    puzzle: &SolvedPuzzle,
+    rules: &[Rule],
+    difficulty: Difficulty,
+) -> Result<bool, InvalidPuzzle> {
+    can_solve_with(puzzle, rules, difficulty, Propagation::default())
+}
+
+// Difficulty as seen by a player whose board propagates with `propagation`.
+fn can_solve_with(
+    puzzle: &SolvedPuzzle,
+    rules: &[Rule],
+    difficulty: Difficulty,
+    propagation: Propagation,
+) -> Result<bool, InvalidPuzzle> {
+    let possibilities = solve(puzzle, rules, propagation)?;
+    if possibilities.is_solved() {
+        Ok(difficulty != Difficulty::Easy
+            || rate_with(puzzle.size(), rules, propagation) == Some(difficulty))
+    } else if difficulty.needs_case_analysis() {
+        let (possibilities, case_analyses) = solve_with_case_analysis(&possibilities, rules);
+        let rating = Rating {
+            case_analyses,
+            ..Rating::default()
+        };
+        Ok(possibilities.is_solved() && rating.difficulty() <= difficulty)
+    } else {
+        Ok(false)
+    }
 }
 
-fn can_solve(puzzle: &SolvedPuzzle, rules: &[Rule]) -> Result<bool, InvalidPuzzle> {
-    let possibilities = solve(puzzle, rules)?;
-    Ok(possibilities.is_solved())
+/// Tells for every rule whether the puzzle keeps its difficulty without it.
+pub fn rule_necessity(
+    puzzle: &SolvedPuzzle,
+    rules: &[Rule],
+    difficulty: Difficulty,
+) -> Vec<Necessity> {
+    necessity(rules, |rules| {
+        can_solve(puzzle, rules, difficulty).unwrap_or(false)
+    })
 }
 
-fn remove_rules(puzzle: &SolvedPuzzle, rules: &[Rule]) -> Result<Vec<Rule>, InvalidPuzzle> {
-    converge_result(rules.to_vec(), |rules| {
-        for index in 0..rules.len() {
+// Rules before `kept` are never removed.
+fn remove_rules(
+    puzzle: &SolvedPuzzle,
+    rules: &[Rule],
+    kept: usize,
+    difficulty: Difficulty,
+    control: &mut GenerationControl,
+) -> Result<Vec<Rule>, GenerationError> {
+    let mut rules = rules.to_vec();
+    loop {
+        control.check()?;
+        let limits = control.limits;
+        // the first removable rule by index, as if tried one by one
+        let removal = (kept..rules.len()).into_par_iter().find_map_first(|index| {
+            if let Err(error) = limits.check() {
+                return Some(Err(error));
+            }
             let mut excluded_rules = rules.clone();
             excluded_rules.remove(index);
-            if can_solve(puzzle, &excluded_rules)? {
-                return Ok(excluded_rules);
+            match can_solve(puzzle, &excluded_rules, difficulty) {
+                Ok(true) => Some(Ok(excluded_rules)),
+                Ok(false) => None,
+                Err(invalid) => Some(Err(invalid.into())),
+            }
+        });
+        match removal {
+            Some(excluded_rules) => {
+                rules = excluded_rules?;
+                control.update(|progress| progress.rules_removed += 1);
             }
+            None => return Ok(rules),
         }
-        Ok(rules)
-    })
+    }
 }
 
-fn generate_rules(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Result<Vec<Rule>, InvalidPuzzle> {
-    let mut rules: Vec<Rule> = Vec::new();
-    while !can_solve(puzzle, &rules)? {
-        let rule = retry(|| generate_rule(rng, puzzle), |rule| !rules.contains(rule));
+// Tries to find a rule not used yet; small puzzles can run out of them.
+const MAX_RULE_TRIES: usize = 10_000;
+
+fn generate_rules(
+    rng: &mut impl Rng,
+    puzzle: &SolvedPuzzle,
+    difficulty: Difficulty,
+    constraints: &RuleConstraints,
+    control: &mut GenerationControl,
+) -> Result<Vec<Rule>, GenerationError> {
+    let mut rules = constraints.required.clone();
+    while !can_solve(puzzle, &rules, difficulty)? {
+        control.check()?;
+        let mix = constraints.next_mix(&rules);
+        let rule = retry_limited(
+            MAX_RULE_TRIES,
+            || generate_rule_with_mix(rng, puzzle, &mix),
+            // no kind of rule is left if there is none
+            |rule| rule.is_none_or(|rule| !rules.contains(&rule)),
+        )
+        .flatten()
+        .ok_or(GenerationError::OutOfRules)?;
         rules.push(rule);
+        control.update(|progress| progress.rules_added += 1);
     }
     Ok(rules)
 }
 
+// Generates rules for the puzzle and removes the ones it can do without.
+fn generate_reduced_rules(
+    rng: &mut impl Rng,
+    puzzle: &SolvedPuzzle,
+    difficulty: Difficulty,
+    constraints: &RuleConstraints,
+    control: &mut GenerationControl,
+) -> Result<Vec<Rule>, GenerationError> {
+    let rules = generate_rules(rng, puzzle, difficulty, constraints, control)?;
+    let kept = constraints.required.len();
+    match control.minimization {
+        Minimization::Greedy => remove_rules(puzzle, &rules, kept, difficulty, control),
+        Minimization::Smallest {
+            max_nodes,
+            propagation,
+        } => {
+            let (required, optional) = rules.split_at(kept);
+            let minimized = minimize_within(optional, max_nodes, control.limits, |optional| {
+                let rules = [required, optional].concat();
+                can_solve_with(puzzle, &rules, difficulty, propagation).unwrap_or(false)
+            })?;
+            control
+                .update(|progress| progress.rules_removed = optional.len() - minimized.rules.len());
+            Ok([required, &minimized.rules].concat())
+        }
+    }
+}
+
+// Makes attempts until one gives a puzzle of the difficulty asked for.
+fn generate_rated(
+    difficulty: Difficulty,
+    control: &mut GenerationControl,
+    mut generate: impl FnMut(
+        &mut GenerationControl,
+    ) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError>,
+) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError> {
+    let mut attempt = 0;
+    loop {
+        control.check()?;
+        attempt += 1;
+        control.update(|progress| {
+            *progress = Progress {
+                attempt,
+                ..Progress::default()
+            }
+        });
+        let (puzzle, rules) = generate(control)?;
+        let propagation = control.minimization.propagation();
+        if rate_with(puzzle.size(), &rules, propagation) == Some(difficulty) {
+            return Ok((puzzle, rules));
+        }
+        if attempt >= MAX_ATTEMPTS {
+            return Err(GenerationError::DifficultyNotReached(difficulty));
+        }
+    }
+}
+
+const MAX_ATTEMPTS: usize = 20;
+
+pub fn puzzle_rng(seed: u64) -> ChaCha8Rng {
+    ChaCha8Rng::seed_from_u64(seed)
+}
+
 pub fn generate_puzzle(
     size: PuzzleSize,
+    difficulty: Difficulty,
+    seed: u64,
+) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError> {
+    generate_puzzle_with_rng(size, difficulty, &mut puzzle_rng(seed))
+}
+
+pub fn generate_puzzle_with_rng(
+    size: PuzzleSize,
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError> {
+    generate_puzzle_with_control(size, difficulty, rng, &mut GenerationControl::new())
+}
+
+/// Generates `count` puzzles on all cores. Puzzle `i` is the one
+/// `generate_puzzle` makes from `seed + i`, whatever the number of threads.
+pub fn generate_batch(
+    size: PuzzleSize,
+    difficulty: Difficulty,
+    count: usize,
+    seed: u64,
+) -> Result<Vec<(SolvedPuzzle, Vec<Rule>)>, GenerationError> {
+    (0..count)
+        .into_par_iter()
+        .map(|index| generate_puzzle(size, difficulty, seed.wrapping_add(index as u64)))
+        .collect()
+}
+
+/// Same as `generate_puzzle_with_rng`, but gives up when `control` says so.
+pub fn generate_puzzle_with_control(
+    size: PuzzleSize,
+    difficulty: Difficulty,
+    rng: &mut impl Rng,
+    control: &mut GenerationControl,
+) -> Result<(SolvedPuzzle, Vec<Rule>), GenerationError> {
+    generate_rated(difficulty, control, |control| {
+        let puzzle = SolvedPuzzle::random(size, rng);
+        let rules = generate_reduced_rules(
+            rng,
+            &puzzle,
+            difficulty,
+            &RuleConstraints::default(),
+            control,
+        )?;
+        Ok((puzzle, rules))
+    })
+}
+
+/// Rules of the given difficulty for a solution chosen in advance.
+pub fn generate_rules_for(
+    solution: &SolvedPuzzle,
+    difficulty: Difficulty,
+    constraints: &RuleConstraints,
+    seed: u64,
+) -> Result<Vec<Rule>, GenerationError> {
+    generate_rules_for_with_control(
+        solution,
+        difficulty,
+        constraints,
+        &mut puzzle_rng(seed),
+        &mut GenerationControl::new(),
+    )
+}
+
+/// Same as `generate_rules_for`, but gives up when `control` says so.
+pub fn generate_rules_for_with_control(
+    solution: &SolvedPuzzle,
+    difficulty: Difficulty,
+    constraints: &RuleConstraints,
     rng: &mut impl Rng,
-) -> Result<(SolvedPuzzle, Vec<Rule>), InvalidPuzzle> {
-    let puzzle = SolvedPuzzle::random(size, rng);
-    let rules = generate_rules(rng, &puzzle)?;
-    let reduced_rules = remove_rules(&puzzle, &rules)?;
-    Ok((puzzle, reduced_rules))
+    control: &mut GenerationControl,
+) -> Result<Vec<Rule>, GenerationError> {
+    if let Some(&rule) = constraints
+        .required
+        .iter()
+        .find(|rule| !rule.is_satisfied_by(solution))
+    {
+        return Err(InvalidPuzzle(rule).into());
+    }
+    if constraints
+        .max_open
+        .is_some_and(|max_open| RuleConstraints::open_count(&constraints.required) > max_open)
+    {
+        return Err(GenerationError::TooManyOpenRules);
+    }
+    let (_, rules) = generate_rated(difficulty, control, |control| {
+        let rules = generate_reduced_rules(rng, solution, difficulty, constraints, control)?;
+        Ok((solution.clone(), rules))
+    })?;
+    Ok(rules)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use rand::thread_rng;
+    use crate::difficulty::rate;
+    use crate::minimize::minimize;
+    use crate::solver::solve_all;
+    use proptest::prelude::*;
+    use rand::{thread_rng, Rng};
 
     #[test]
     fn test_eq_generate_puzzle() {
         let size = PuzzleSize {
             kinds: 6,
             values: 6,
+            topology: Topology::Line,
         };
         let mut rng = thread_rng();
-        let (_puzzle, rules) = generate_puzzle(size, &mut rng).unwrap();
-        assert!(rules.len() > 0);
+        let (_puzzle, rules) =
+            generate_puzzle_with_rng(size, Difficulty::Medium, &mut rng).unwrap();
+        assert!(!rules.is_empty());
+    }
+
+    #[test]
+    fn test_same_seed_gives_same_puzzle() {
+        let size = PuzzleSize {
+            kinds: 5,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let seed = thread_rng().gen();
+        let (puzzle1, rules1) = generate_puzzle(size, Difficulty::Medium, seed).unwrap();
+        let (puzzle2, rules2) = generate_puzzle(size, Difficulty::Medium, seed).unwrap();
+        assert_eq!(puzzle1, puzzle2);
+        assert_eq!(rules1, rules2);
+    }
+
+    #[test]
+    fn test_batch_is_deterministic() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 4,
+            topology: Topology::Line,
+        };
+        let seed = u64::MAX - 2;
+        let batch_on = |threads| {
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap()
+                .install(|| generate_batch(size, Difficulty::Medium, 6, seed))
+                .unwrap()
+        };
+        let batch = batch_on(1);
+        assert_eq!(batch_on(4), batch);
+        let one_by_one: Vec<_> = (0..6)
+            .map(|index| generate_puzzle(size, Difficulty::Medium, seed.wrapping_add(index)))
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(batch, one_by_one);
+    }
+
+    #[test]
+    fn test_smallest_rules_are_not_more_than_greedy() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 4,
+            topology: Topology::Line,
+        };
+        let difficulty = Difficulty::Medium;
+        for seed in 0..2 {
+            let mut rng = puzzle_rng(seed);
+            let mut control = GenerationControl::new();
+            let puzzle = SolvedPuzzle::random(size, &mut rng);
+            let rules = generate_rules(
+                &mut rng,
+                &puzzle,
+                difficulty,
+                &RuleConstraints::default(),
+                &mut control,
+            )
+            .unwrap();
+            let greedy = remove_rules(&puzzle, &rules, 0, difficulty, &mut control).unwrap();
+
+            let solves = |rules: &[Rule]| can_solve(&puzzle, rules, difficulty).unwrap();
+            let smallest = minimize(&rules, usize::MAX, solves);
+            assert!(smallest.proven);
+            assert!(solves(&smallest.rules));
+            assert!(smallest.rules.len() <= greedy.len());
+            assert!(rule_necessity(&puzzle, &smallest.rules, difficulty)
+                .iter()
+                .all(|necessity| *necessity == Necessity::Necessary));
+
+            // a stronger board never needs more rules
+            let arc = minimize(&rules, usize::MAX, |rules| {
+                can_solve_with(&puzzle, rules, difficulty, Propagation::ArcConsistency).unwrap()
+            });
+            assert!(arc.rules.len() <= smallest.rules.len());
+        }
+    }
+
+    #[test]
+    fn test_generate_smallest_puzzle() {
+        let size = PuzzleSize {
+            kinds: 3,
+            values: 4,
+            topology: Topology::Line,
+        };
+        for propagation in [Propagation::Singles, Propagation::ArcConsistency] {
+            let mut control = GenerationControl::new().with_minimization(Minimization::Smallest {
+                max_nodes: 100,
+                propagation,
+            });
+            let (puzzle, rules) = generate_puzzle_with_control(
+                size,
+                Difficulty::Easy,
+                &mut puzzle_rng(1),
+                &mut control,
+            )
+            .unwrap();
+            assert!(solve(&puzzle, &rules, propagation).unwrap().is_solved());
+            assert_eq!(rate_with(size, &rules, propagation), Some(Difficulty::Easy));
+        }
+    }
+
+    #[test]
+    fn test_rules_for_solution_follow_constraints() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let solution = SolvedPuzzle::random(size, &mut puzzle_rng(1));
+        let required = Rule::Open(2, solution.get(Kind(1), 2));
+        let constraints = RuleConstraints {
+            required: vec![required],
+            forbidden: vec![RuleKind::Between, RuleKind::Either],
+            max_open: Some(1),
+            mix: RuleMix::default().with_weight(RuleKind::Under, 10),
+        };
+        for seed in 0..5 {
+            let rules =
+                generate_rules_for(&solution, Difficulty::Medium, &constraints, seed).unwrap();
+            assert_eq!(rules[0], required);
+            assert!(rules[1..].iter().all(|rule| !matches!(
+                rule.kind(),
+                RuleKind::Open | RuleKind::Between | RuleKind::Either
+            )));
+            assert_eq!(solve_all(size, &rules).unwrap(), vec![solution.clone()]);
+        }
+    }
+
+    #[test]
+    fn test_rules_for_solution_check_required_rules() {
+        let size = PuzzleSize::default();
+        let solution = SolvedPuzzle::random(size, &mut puzzle_rng(1));
+        let open = |col| Rule::Open(col, solution.get(Kind(0), col));
+        let false_rule = Rule::Open(0, solution.get(Kind(0), 1));
+        let generate = |required, max_open| {
+            let constraints = RuleConstraints {
+                required,
+                max_open,
+                ..RuleConstraints::default()
+            };
+            generate_rules_for(&solution, Difficulty::Medium, &constraints, 1)
+        };
+        assert!(matches!(
+            generate(vec![false_rule], None),
+            Err(GenerationError::InvalidPuzzle(_))
+        ));
+        assert!(matches!(
+            generate(vec![open(0), open(1)], Some(1)),
+            Err(GenerationError::TooManyOpenRules)
+        ));
+        assert!(generate(vec![open(0), open(1)], Some(2)).is_ok());
+    }
+
+    #[test]
+    fn test_cancelled_generation() {
+        let cancel = AtomicBool::new(true);
+        let result = generate_puzzle_with_control(
+            PuzzleSize::default(),
+            Difficulty::Medium,
+            &mut puzzle_rng(1),
+            &mut GenerationControl::new().with_cancel(&cancel),
+        );
+        assert!(matches!(result, Err(GenerationError::Cancelled)));
+    }
+
+    #[test]
+    fn test_timed_out_generation() {
+        let result = generate_puzzle_with_control(
+            PuzzleSize::default(),
+            Difficulty::Medium,
+            &mut puzzle_rng(1),
+            &mut GenerationControl::new().with_deadline(Instant::now()),
+        );
+        assert!(matches!(result, Err(GenerationError::TimedOut)));
+    }
+
+    #[test]
+    fn test_generation_progress() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 4,
+            topology: Topology::Line,
+        };
+        let mut reports = Vec::new();
+        let mut control = GenerationControl::new().with_progress(|progress| reports.push(progress));
+        let (_puzzle, rules) = generate_puzzle_with_control(
+            size,
+            Difficulty::Medium,
+            &mut puzzle_rng(1),
+            &mut control,
+        )
+        .unwrap();
+        let last = control.progress();
+        drop(control);
+        assert_eq!(reports.last(), Some(&last));
+        assert_eq!(rules.len(), last.rules_added - last.rules_removed);
+        // the same puzzle as without control
+        assert_eq!(
+            generate_puzzle(size, Difficulty::Medium, 1).unwrap().1,
+            rules
+        );
+    }
+
+    // Small puzzles cannot be anything but easy.
+    fn generate_reachable(size: PuzzleSize, seed: u64) -> (SolvedPuzzle, Vec<Rule>) {
+        for difficulty in [Difficulty::Medium, Difficulty::Easy] {
+            match generate_puzzle(size, difficulty, seed) {
+                Ok((puzzle, rules)) => {
+                    let rated = rate(size, &rules).map(|rating| rating.difficulty());
+                    assert_eq!(rated, Some(difficulty));
+                    return (puzzle, rules);
+                }
+                Err(GenerationError::DifficultyNotReached(_)) => {}
+                Err(error) => panic!("{}", error),
+            }
+        }
+        panic!("Neither medium nor easy puzzle of size {:?}", size);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        #[test]
+        fn test_rectangular_puzzle_solves(kinds in 1_u8..=6, values in 2_u8..=8, seed: u64) {
+            prop_assume!(kinds != values);
+            let size = PuzzleSize { kinds, values, topology: Topology::Line };
+            let (puzzle, rules) = generate_reachable(size, seed);
+            let possibilities = solve(&puzzle, &rules, Propagation::Singles).unwrap();
+            prop_assert!(possibilities.is_solved());
+            prop_assert!(possibilities.is_valid(&puzzle));
+        }
+
+        #[test]
+        fn test_ring_puzzle_solves(kinds in 1_u8..=6, values in 2_u8..=8, seed: u64) {
+            let size = PuzzleSize { kinds, values, topology: Topology::Ring };
+            let (puzzle, rules) = generate_reachable(size, seed);
+            let possibilities = solve(&puzzle, &rules, Propagation::Singles).unwrap();
+            prop_assert!(possibilities.is_solved());
+            prop_assert!(possibilities.is_valid(&puzzle));
+            prop_assert!(rules.iter().all(|rule| !matches!(rule, Rule::Direction(..))));
+        }
+
+        #[test]
+        fn test_grid_puzzle_solves(kinds in 1_u8..=5, width in 2_u8..=3, rows in 2_u8..=3, seed: u64) {
+            let size = PuzzleSize { kinds, values: width * rows, topology: Topology::Grid(width) };
+            let (puzzle, rules) = generate_reachable(size, seed);
+            let possibilities = solve(&puzzle, &rules, Propagation::Singles).unwrap();
+            prop_assert!(possibilities.is_solved());
+            prop_assert!(possibilities.is_valid(&puzzle));
+            prop_assert!(rules.iter().all(|rule| rule.is_satisfied_by(&puzzle)));
+        }
     }
 }
@@ -1,6 +1,6 @@
 use crate::bitset::BitSet;
 use crate::u4::U4;
-use crate::util::{converge::converge, num_to_str::num_to_str};
+use crate::util::{converge::converge, num_to_str::num_to_str, retry::retry};
 use rand::{
     distributions::{Distribution, WeightedIndex},
     seq::SliceRandom,
@@ -8,21 +8,73 @@ use rand::{
 };
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
-use std::convert::TryInto;
+use std::convert::{TryFrom, TryInto};
 use std::fmt;
 
-fn only<T>(values: &[T]) -> Option<&T> {
-    if values.len() == 1 {
-        values.first()
-    } else {
-        None
+fn combinations(items: &[usize], count: usize) -> Vec<Vec<usize>> {
+    if count == 0 {
+        return vec![Vec::new()];
+    }
+    (0..items.len())
+        .flat_map(|index| {
+            combinations(&items[index + 1..], count - 1)
+                .into_iter()
+                .map(move |mut rest| {
+                    rest.insert(0, items[index]);
+                    rest
+                })
+        })
+        .collect()
+}
+
+// the largest naked or hidden subset looked for in a row
+const MAX_SUBSET: usize = 3;
+
+/// How much deduction is done automatically after every change.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Propagation {
+    /// Naked and hidden singles within a row.
+    #[default]
+    Singles,
+    /// Naked and hidden pairs and triples within a row.
+    Subsets,
+    /// Subsets plus arc consistency of binary rules across rows.
+    ArcConsistency,
+}
+
+impl Propagation {
+    pub const ALL: [Propagation; 3] = [
+        Propagation::Singles,
+        Propagation::Subsets,
+        Propagation::ArcConsistency,
+    ];
+}
+
+impl fmt::Display for Propagation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Propagation::Singles => write!(f, "singles"),
+            Propagation::Subsets => write!(f, "subsets"),
+            Propagation::ArcConsistency => write!(f, "arc-consistency"),
+        }
     }
 }
 
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub enum Topology {
+    #[default]
+    Line,
+    Ring, // the last column is next to the first one
+    /// Houses are laid out row by row in a grid of the given width.
+    Grid(u8),
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
 pub struct PuzzleSize {
     pub kinds: u8,
     pub values: u8,
+    #[serde(default)]
+    pub topology: Topology,
 }
 
 impl PuzzleSize {
@@ -31,7 +83,74 @@ impl PuzzleSize {
     }
 
     pub fn iter_values(&self) -> impl Iterator<Item = Value> {
-        (0..self.kinds).map(Value)
+        (0..self.values).map(Value)
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.kinds > 0
+            && self.values > 0
+            && match self.topology {
+                Topology::Grid(width) => width > 0 && self.values.is_multiple_of(width),
+                _ => true,
+            }
+    }
+
+    /// Number of columns in a row of houses.
+    pub fn width(&self) -> u8 {
+        match self.topology {
+            Topology::Grid(width) => width,
+            _ => self.values,
+        }
+    }
+
+    pub fn grid_rows(&self) -> u8 {
+        self.values / self.width()
+    }
+
+    /// Column `offset` steps to the right of `col`, if there is one.
+    pub fn shift(&self, col: u8, offset: i8) -> Option<u8> {
+        let values = i16::from(self.values);
+        let target = i16::from(col) + i16::from(offset);
+        match self.topology {
+            Topology::Line => (0..values).contains(&target).then_some(target as u8),
+            Topology::Ring => Some(target.rem_euclid(values) as u8),
+            Topology::Grid(width) => {
+                let x = i16::from(col % width) + i16::from(offset);
+                (0..i16::from(width)).contains(&x).then_some(target as u8)
+            }
+        }
+    }
+
+    /// House `offset` rows below `col`, if there is one.
+    pub fn shift_vertically(&self, col: u8, offset: i8) -> Option<u8> {
+        let target = i16::from(col) + i16::from(offset) * i16::from(self.width());
+        (0..i16::from(self.values))
+            .contains(&target)
+            .then_some(target as u8)
+    }
+
+    pub fn distance(&self, col1: u8, col2: u8) -> u8 {
+        let distance = col1.abs_diff(col2);
+        match self.topology {
+            Topology::Line => distance,
+            Topology::Ring => distance.min(self.values - distance),
+            Topology::Grid(width) => {
+                (col1 % width).abs_diff(col2 % width) + (col1 / width).abs_diff(col2 / width)
+            }
+        }
+    }
+
+    pub fn max_distance(&self) -> u8 {
+        match self.topology {
+            Topology::Line => self.values - 1,
+            Topology::Ring => self.values / 2,
+            Topology::Grid(width) => width - 1 + self.grid_rows() - 1,
+        }
+    }
+
+    pub fn at_distance(&self, col: u8, distance: u8) -> impl Iterator<Item = u8> {
+        let size = *self;
+        (0..self.values).filter(move |other| size.distance(col, *other) == distance)
     }
 }
 
@@ -40,6 +159,7 @@ impl Default for PuzzleSize {
         Self {
             kinds: 6,
             values: 6,
+            topology: Topology::Line,
         }
     }
 }
@@ -120,7 +240,7 @@ impl<T: Clone> KindMap<T> {
     }
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub struct SolvedPuzzle {
     size: PuzzleSize,
     values: KindMap<Vec<Value>>,
@@ -152,6 +272,26 @@ impl SolvedPuzzle {
             value: self.get_value(row, col),
         }
     }
+
+    pub fn find(&self, thing: Thing) -> u8 {
+        self.values
+            .get(thing.row)
+            .iter()
+            .position(|value| *value == thing.value)
+            .expect("Every value is placed") as u8
+    }
+
+    pub fn from_possibilities(possibilities: &Possibilities) -> Option<Self> {
+        let size = possibilities.size();
+        let mut values = KindMap::new(size.kinds, Vec::new());
+        for kind in size.iter_kinds() {
+            let kvalues = (0..size.values)
+                .map(|col| possibilities.get_defined(col, kind))
+                .collect::<Option<Vec<Value>>>()?;
+            values.set(kind, kvalues);
+        }
+        Some(Self { size, values })
+    }
 }
 
 fn value_to_u4(value: Value) -> U4 {
@@ -201,110 +341,335 @@ impl ValueSet {
     pub fn remove(&mut self, value: Value) -> bool {
         self.0.remove(value_to_u4(value))
     }
-}
 
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
-struct PossibilitiesRow(Vec<ValueSet>);
+    pub fn intersection(&self, other: &ValueSet) -> ValueSet {
+        Self(self.0.intersection(other.0))
+    }
 
-impl PossibilitiesRow {
-    fn new(values: u8) -> Self {
-        Self(vec![ValueSet::full(values); values as usize])
+    pub fn union(&self, other: &ValueSet) -> ValueSet {
+        Self(self.0.union(other.0))
     }
+}
 
-    fn columns(&self) -> usize {
-        self.0.len()
+const MAX_KINDS: usize = 16;
+const MAX_VALUES: usize = 16;
+
+fn value_in_columns(row: &[ValueSet], value: Value) -> BitSet {
+    let mut cols = BitSet::empty();
+    for (col, cell) in row.iter().enumerate() {
+        if cell.contains(value) {
+            cols.add(col.try_into().unwrap());
+        }
     }
+    cols
+}
+
+// Row checks narrow the cells in place and tell whether anything changed.
 
-    fn exclude(&self, col: u8, element: Value) -> Self {
-        let mut new_row = self.clone();
-        new_row.0[usize::from(col)].remove(element);
-        new_row.check_singles()
+fn check_single_value_in_a_cell(row: &mut [ValueSet]) -> bool {
+    let mut changed = false;
+    for col in 0..row.len() {
+        if let Some(value) = row[col].get_single() {
+            // there is only one element in cell but it used somewhere else
+            for (i, cell) in row.iter_mut().enumerate() {
+                if i != col {
+                    changed |= cell.remove(value);
+                }
+            }
+        }
     }
+    changed
+}
 
-    fn set(&self, col: u8, element: Value) -> Self {
-        let mut new_row = self.clone();
-        new_row.0[usize::from(col)] = ValueSet::single(element);
-        new_row.check_singles()
+fn check_single_value_in_a_row(row: &mut [ValueSet]) -> bool {
+    let mut changed = false;
+    // check for single element without exclusive cell
+    for value_index in 0..row.len() {
+        let value = Value(value_index as u8);
+        if let Some(col) = value_in_columns(row, value).get_single() {
+            let cell = &mut row[usize::from(col)];
+            if cell.size() > 1 {
+                *cell = ValueSet::single(value);
+                changed = true;
+            }
+        }
     }
+    changed
+}
 
-    fn value_in_columns(&self, value: Value) -> Vec<u8> {
-        (0..self.columns())
-            .filter(|col| self.0[*col].contains(value))
-            .map(|col| col as u8)
-            .collect()
+fn check_singles(row: &mut [ValueSet]) -> bool {
+    let mut changed = false;
+    while check_single_value_in_a_cell(row) | check_single_value_in_a_row(row) {
+        changed = true;
     }
+    changed
+}
 
-    fn check_single_value_in_a_cell(&self) -> Self {
-        let mut new_row = self.clone();
-        for col in 0..self.columns() {
-            if let Some(value) = self.0[col].get_single() {
-                // there is only one element in cell but it used somewhere else
-                for i in 0..self.columns() {
-                    if i != col {
-                        new_row.0[i].remove(value);
+fn check_naked_subsets(row: &mut [ValueSet]) -> bool {
+    let mut changed = false;
+    let open: Vec<usize> = (0..row.len()).filter(|col| row[*col].size() > 1).collect();
+    for count in 2..=MAX_SUBSET {
+        for cols in combinations(&open, count) {
+            let values = cols
+                .iter()
+                .fold(ValueSet::empty(), |acc, col| acc.union(&row[*col]));
+            if values.size() != count {
+                continue;
+            }
+            // these values are used up by the subset cells
+            for (col, cell) in row.iter_mut().enumerate() {
+                if !cols.contains(&col) {
+                    for value in values.iter() {
+                        changed |= cell.remove(value);
                     }
                 }
             }
         }
-        new_row
     }
+    changed
+}
 
-    fn check_single_value_in_a_row(&self) -> Self {
-        let mut new_row = self.clone();
-        // check for single element without exclusive cell
-        for value_index in 0..self.columns() {
-            let value = Value(value_index as u8);
-            if let Some(col) = only(&new_row.value_in_columns(value)) {
-                new_row.0[*col as usize] = ValueSet::single(value);
+fn check_hidden_subsets(row: &mut [ValueSet]) -> bool {
+    let mut changed = false;
+    let open: Vec<usize> = (0..row.len())
+        .filter(|value| value_in_columns(row, Value(*value as u8)).len() > 1)
+        .collect();
+    for count in 2..=MAX_SUBSET {
+        for values in combinations(&open, count) {
+            let mut subset = ValueSet::empty();
+            let mut cols = BitSet::empty();
+            for value in values {
+                subset.add(Value(value as u8));
+                cols = cols.union(value_in_columns(row, Value(value as u8)));
+            }
+            if cols.len() != count {
+                continue;
+            }
+            // these cells are the only place for the subset values
+            for col in cols.iter() {
+                let cell = &mut row[usize::from(col)];
+                let narrowed = cell.intersection(&subset);
+                changed |= narrowed != *cell;
+                *cell = narrowed;
             }
         }
-        new_row
     }
+    changed
+}
 
-    fn check_singles(&self) -> Self {
-        converge(self.clone(), |p| {
-            p.check_single_value_in_a_cell()
-                .check_single_value_in_a_row()
-        })
+fn check_row(row: &mut [ValueSet], propagation: Propagation) {
+    match propagation {
+        Propagation::Singles => {
+            check_singles(row);
+        }
+        Propagation::Subsets | Propagation::ArcConsistency => {
+            while check_singles(row) | check_naked_subsets(row) | check_hidden_subsets(row) {}
+        }
     }
+}
 
-    fn is_solved(&self) -> bool {
-        self.0.iter().all(|s| s.size() == 1)
-    }
+fn is_row_solved(row: &[ValueSet]) -> bool {
+    row.iter().all(|s| s.size() == 1)
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, Serialize, Deserialize)]
+fn is_row_consistent(row: &[ValueSet]) -> bool {
+    // every cell has a candidate and every value has a cell
+    row.iter().all(|s| s.size() > 0)
+        && row
+            .iter()
+            .fold(ValueSet::empty(), |acc, cell| acc.union(cell))
+            .size()
+            == row.len()
+}
+
+/// Candidates left for every cell of the puzzle.
+///
+/// Cells live in a fixed-size bitboard, so copying the whole board does
+/// not allocate and changes can be made in place.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "PossibilitiesData", into = "PossibilitiesData")]
 pub struct Possibilities {
     size: PuzzleSize,
-    rows: KindMap<PossibilitiesRow>,
+    cells: [[ValueSet; MAX_VALUES]; MAX_KINDS],
+    propagation: Propagation,
+}
+
+// Only the cells in use, the rest is always empty.
+impl fmt::Debug for Possibilities {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Possibilities")
+            .field("size", &self.size)
+            .field("rows", &PossibilitiesData::from(self.clone()).rows)
+            .field("propagation", &self.propagation)
+            .finish()
+    }
+}
+
+// The serialized form keeps only the cells in use, the way saved games always stored them.
+#[derive(Serialize, Deserialize)]
+struct PossibilitiesData {
+    size: PuzzleSize,
+    rows: Vec<Vec<ValueSet>>,
+    #[serde(default)]
+    propagation: Propagation,
+}
+
+impl TryFrom<PossibilitiesData> for Possibilities {
+    type Error = String;
+
+    fn try_from(data: PossibilitiesData) -> Result<Self, Self::Error> {
+        let size = data.size;
+        if usize::from(size.kinds) > MAX_KINDS || usize::from(size.values) > MAX_VALUES {
+            return Err(format!("{}×{} board is too large", size.values, size.kinds));
+        }
+        if data.rows.len() != usize::from(size.kinds)
+            || data
+                .rows
+                .iter()
+                .any(|row| row.len() != usize::from(size.values))
+        {
+            return Err("Rows do not match the board size".to_string());
+        }
+        let mut possibilities = Possibilities::new(size);
+        possibilities.propagation = data.propagation;
+        for (kind, row) in data.rows.into_iter().enumerate() {
+            possibilities.cells[kind][..row.len()].copy_from_slice(&row);
+        }
+        Ok(possibilities)
+    }
+}
+
+impl From<Possibilities> for PossibilitiesData {
+    fn from(possibilities: Possibilities) -> Self {
+        Self {
+            size: possibilities.size,
+            rows: possibilities
+                .size
+                .iter_kinds()
+                .map(|kind| possibilities.row(kind).to_vec())
+                .collect(),
+            propagation: possibilities.propagation,
+        }
+    }
 }
 
 impl Possibilities {
     pub fn new(size: PuzzleSize) -> Self {
+        assert!(usize::from(size.kinds) <= MAX_KINDS && usize::from(size.values) <= MAX_VALUES);
+        let mut cells = [[ValueSet::empty(); MAX_VALUES]; MAX_KINDS];
+        for row in &mut cells[..usize::from(size.kinds)] {
+            row[..usize::from(size.values)].fill(ValueSet::full(size.values));
+        }
         Self {
             size,
-            rows: KindMap::new(size.kinds, PossibilitiesRow::new(size.values)),
+            cells,
+            propagation: Propagation::default(),
         }
     }
 
-    pub fn exclude(&self, col: u8, kind: Kind, value: Value) -> Self {
-        let row = self.rows.get(kind).exclude(col, value);
+    pub fn size(&self) -> PuzzleSize {
+        self.size
+    }
+
+    pub fn propagation(&self) -> Propagation {
+        self.propagation
+    }
+
+    fn row(&self, kind: Kind) -> &[ValueSet] {
+        &self.cells[usize::from(kind.0)][..usize::from(self.size.values)]
+    }
+
+    fn row_mut(&mut self, kind: Kind) -> &mut [ValueSet] {
+        &mut self.cells[usize::from(kind.0)][..usize::from(self.size.values)]
+    }
 
+    /// Same candidates deduced further with the given strength. Weakening the
+    /// strength keeps what is already deduced.
+    pub fn with_propagation(&self, propagation: Propagation) -> Self {
         let mut new = self.clone();
-        new.rows.set(kind, row);
+        new.propagation = propagation;
+        for kind in self.size.iter_kinds() {
+            check_row(new.row_mut(kind), propagation);
+        }
+        new
+    }
+
+    pub fn exclude(&self, col: u8, kind: Kind, value: Value) -> Self {
+        let mut new = self.clone();
+        new.exclude_in_place(col, kind, value);
         new
     }
 
     pub fn set(&self, col: u8, kind: Kind, value: Value) -> Self {
-        let row = self.rows.get(kind).set(col, value);
+        let mut new = self.clone();
+        new.set_in_place(col, kind, value);
+        new
+    }
+
+    pub fn exclude_in_place(&mut self, col: u8, kind: Kind, value: Value) {
+        let propagation = self.propagation;
+        let row = self.row_mut(kind);
+        if row[usize::from(col)].remove(value) {
+            check_row(row, propagation);
+        }
+    }
+
+    pub fn set_in_place(&mut self, col: u8, kind: Kind, value: Value) {
+        let propagation = self.propagation;
+        let row = self.row_mut(kind);
+        row[usize::from(col)] = ValueSet::single(value);
+        check_row(row, propagation);
+    }
+
+    fn without(&self, col: u8, kind: Kind, value: Value) -> Self {
+        let mut new = self.clone();
+        new.row_mut(kind)[usize::from(col)].remove(value);
+        new
+    }
 
+    fn with_only(&self, col: u8, kind: Kind, value: Value) -> Self {
         let mut new = self.clone();
-        new.rows.set(kind, row);
+        new.row_mut(kind)[usize::from(col)] = ValueSet::single(value);
         new
     }
 
+    fn changes_to(&self, other: &Possibilities) -> Vec<Change> {
+        let mut changes = Vec::new();
+        for kind in self.size.iter_kinds() {
+            for col in 0..self.size.values {
+                let before = self.get_possible(col, kind);
+                let after = other.get_possible(col, kind);
+                if before == after {
+                    continue;
+                }
+                match after.get_single() {
+                    Some(value) if before.size() > 1 => {
+                        changes.push(Change::Set(col, Thing { row: kind, value }))
+                    }
+                    _ => changes.extend(
+                        before
+                            .iter()
+                            .filter(|value| !after.contains(*value))
+                            .map(|value| Change::Exclude(col, Thing { row: kind, value })),
+                    ),
+                }
+            }
+        }
+        changes
+    }
+
+    /// Kinds with at least one cell different in `other`.
+    pub fn changed_kinds<'a>(
+        &'a self,
+        other: &'a Possibilities,
+    ) -> impl Iterator<Item = Kind> + 'a {
+        self.size
+            .iter_kinds()
+            .filter(move |kind| self.row(*kind) != other.row(*kind))
+    }
+
     pub fn get_possible(&self, col: u8, row: Kind) -> ValueSet {
-        self.rows.get(row).0[usize::from(col)]
+        self.cells[usize::from(row.0)][usize::from(col)]
     }
 
     pub fn is_possible(&self, col: u8, thing: Thing) -> bool {
@@ -319,10 +684,40 @@ impl Possibilities {
         self.get_possible(col, row).get_single()
     }
 
+    pub fn is_at(&self, col: u8, thing: Thing) -> bool {
+        self.get_defined(col, thing.row) == Some(thing.value)
+    }
+
     pub fn is_solved(&self) -> bool {
         self.size
             .iter_kinds()
-            .all(|kind| self.rows.get(kind).is_solved())
+            .all(|kind| is_row_solved(self.row(kind)))
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.size
+            .iter_kinds()
+            .all(|kind| is_row_consistent(self.row(kind)))
+    }
+
+    pub fn intersect(&self, other: &Possibilities) -> Self {
+        self.combine(other, ValueSet::intersection)
+    }
+
+    pub fn unite(&self, other: &Possibilities) -> Self {
+        self.combine(other, ValueSet::union)
+    }
+
+    fn combine(&self, other: &Possibilities, cell: fn(&ValueSet, &ValueSet) -> ValueSet) -> Self {
+        let mut new = self.clone();
+        for kind in self.size.iter_kinds() {
+            let row = new.row_mut(kind);
+            for (cell1, cell2) in row.iter_mut().zip(other.row(kind)) {
+                *cell1 = cell(cell1, cell2);
+            }
+            check_row(row, self.propagation);
+        }
+        new
     }
 
     pub fn is_valid(&self, puzzle: &SolvedPuzzle) -> bool {
@@ -338,124 +733,727 @@ impl Possibilities {
     }
 }
 
-fn gen_two(rng: &mut impl Rng, max: u8) -> (u8, u8) {
-    let v1: u8 = rng.gen_range(0..(max - 1));
-    let v2: u8 = rng.gen_range((v1 + 1)..max);
-    (v1, v2)
-}
-
-#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
-pub enum Rule {
-    Near(Thing, Thing),
-    Direction(Thing, Thing),
-    Open(u8, Thing), // column
-    Under(Thing, Thing),
-    Between(Thing, Thing, Thing),
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Change {
+    Exclude(u8, Thing), // column
+    Set(u8, Thing),     // column
 }
 
-impl Rule {
-    pub fn is_non_trivial(self) -> bool {
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
-            Rule::Open(..) => false,
-            _ => true,
+            Change::Exclude(col, thing) => write!(f, "{} is not at column {}", thing, col + 1),
+            Change::Set(col, thing) => write!(f, "{} is at column {}", thing, col + 1),
         }
     }
 }
 
-fn generate_near_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
-    let size = puzzle.size();
-    let row1 = rng.gen_range(0..size.kinds);
-    let row2 = rng.gen_range(0..size.kinds);
-    let first_col = rng.gen_range(0..(size.values - 1));
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Elimination {
+    pub change: Change,
+    pub propagations: Vec<Change>,
+}
 
-    let thing1 = puzzle.get(Kind(row1), first_col);
-    let thing2 = puzzle.get(Kind(row2), first_col + 1);
+struct Board<'t> {
+    pos: Possibilities,
+    trace: Option<&'t mut Vec<Elimination>>,
+}
 
-    if rng.gen() {
-        Rule::Near(thing1, thing2)
-    } else {
-        Rule::Near(thing2, thing1)
+impl Board<'_> {
+    fn exclude(&mut self, col: u8, thing: Thing) {
+        if !self.pos.is_possible(col, thing) {
+            return;
+        }
+        let Some(ref mut trace) = self.trace else {
+            self.pos.exclude_in_place(col, thing.row, thing.value);
+            return;
+        };
+        let raw = self.pos.without(col, thing.row, thing.value);
+        self.pos.exclude_in_place(col, thing.row, thing.value);
+        trace.push(Elimination {
+            change: Change::Exclude(col, thing),
+            propagations: raw.changes_to(&self.pos),
+        });
     }
-}
 
-fn generate_direction_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
-    let size = puzzle.size();
-    let row1 = rng.gen_range(0..size.kinds);
-    let row2 = rng.gen_range(0..size.kinds);
-    let (col1, col2) = gen_two(rng, size.values);
+    fn set(&mut self, col: u8, thing: Thing) {
+        if self.pos.is_defined(col, thing.row) && self.pos.is_possible(col, thing) {
+            return;
+        }
+        let Some(ref mut trace) = self.trace else {
+            self.pos.set_in_place(col, thing.row, thing.value);
+            return;
+        };
+        let raw = self.pos.with_only(col, thing.row, thing.value);
+        self.pos.set_in_place(col, thing.row, thing.value);
+        trace.push(Elimination {
+            change: Change::Set(col, thing),
+            propagations: raw.changes_to(&self.pos),
+        });
+    }
 
-    let thing1 = puzzle.get(Kind(row1), col1);
-    let thing2 = puzzle.get(Kind(row2), col2);
-    Rule::Direction(thing1, thing2)
-}
+    fn narrow_to(&mut self, target: &Possibilities) {
+        let size = self.pos.size;
+        for row in size.iter_kinds() {
+            for col in 0..size.values {
+                for value in self.pos.get_possible(col, row).iter() {
+                    if !target.is_possible(col, Thing { row, value }) {
+                        self.exclude(col, Thing { row, value });
+                    }
+                }
+            }
+        }
+    }
 
-fn generate_open_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
-    let size = puzzle.size();
-    let row = rng.gen_range(0..size.kinds);
-    let col = rng.gen_range(0..size.values);
+    fn converge(&mut self, step: impl Fn(&mut Self)) {
+        loop {
+            let previous = self.pos.clone();
+            step(self);
+            if self.pos == previous {
+                return;
+            }
+        }
+    }
+}
 
-    let thing = puzzle.get(Kind(row), col);
-    Rule::Open(col, thing)
+fn random_thing(rng: &mut impl Rng, size: PuzzleSize) -> Thing {
+    Thing {
+        row: Kind(rng.gen_range(0..size.kinds)),
+        value: Value(rng.gen_range(0..size.values)),
+    }
 }
 
-fn generate_under_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
-    let size = puzzle.size();
-    let col: u8 = rng.gen_range(0..size.values);
-    let (row1, row2) = gen_two(rng, size.kinds);
+fn gen_columns_apart(rng: &mut impl Rng, size: PuzzleSize, distance: u8) -> (u8, u8) {
+    let first_col = match size.topology {
+        Topology::Line => rng.gen_range(0..(size.values - distance)),
+        Topology::Ring => rng.gen_range(0..size.values),
+        Topology::Grid(_) => retry(
+            || rng.gen_range(0..size.values),
+            |col| size.shift(*col, distance as i8).is_some(),
+        ),
+    };
+    (first_col, size.shift(first_col, distance as i8).unwrap())
+}
 
-    let thing1 = puzzle.get(Kind(row1), col);
-    let thing2 = puzzle.get(Kind(row2), col);
-    Rule::Under(thing1, thing2)
+fn gen_houses_apart(rng: &mut impl Rng, size: PuzzleSize, distance: u8) -> (u8, u8) {
+    match size.topology {
+        Topology::Grid(_) => retry(
+            || (rng.gen_range(0..size.values), rng.gen_range(0..size.values)),
+            |(col1, col2)| size.distance(*col1, *col2) == distance,
+        ),
+        _ => gen_columns_apart(rng, size, distance),
+    }
 }
 
-fn generate_between_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
-    let size = puzzle.size();
-    let row1 = rng.gen_range(0..size.kinds);
-    let row2 = rng.gen_range(0..size.kinds);
-    let row3 = rng.gen_range(0..size.kinds);
-    let first_col = rng.gen_range(0..(size.values - 2));
+fn gen_two(rng: &mut impl Rng, max: u8) -> (u8, u8) {
+    let v1: u8 = rng.gen_range(0..(max - 1));
+    let v2: u8 = rng.gen_range((v1 + 1)..max);
+    (v1, v2)
+}
 
-    let thing1 = puzzle.get(Kind(row1), first_col);
-    let thing2 = puzzle.get(Kind(row2), first_col + 1);
-    let thing3 = puzzle.get(Kind(row3), first_col + 2);
+/// Simple relation used as a part of a compound rule.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Clause {
+    At(u8, Thing), // column
+    Near(Thing, Thing),
+    Under(Thing, Thing),
+    LeftOf(Thing, Thing),
+}
 
-    if rng.gen() {
-        Rule::Between(thing1, thing2, thing3)
-    } else {
-        Rule::Between(thing3, thing2, thing1)
+impl Clause {
+    pub fn rule(self) -> Rule {
+        match self {
+            Clause::At(col, thing) => Rule::Open(col, thing),
+            Clause::Near(thing1, thing2) => Rule::Near(thing1, thing2),
+            Clause::Under(thing1, thing2) => Rule::Under(thing1, thing2),
+            Clause::LeftOf(thing1, thing2) => Rule::LeftOf(thing1, thing2),
+        }
     }
-}
 
-pub fn generate_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
-    let weights = [4_u32, 1, 2, 4, 3];
-    let dist = WeightedIndex::new(&weights).unwrap();
-    match dist.sample(rng) {
-        0 => generate_near_rule(rng, puzzle),
-        1 => generate_open_rule(rng, puzzle),
-        2 => generate_under_rule(rng, puzzle),
-        3 => generate_direction_rule(rng, puzzle),
-        4 => generate_between_rule(rng, puzzle),
-        _ => unreachable!(),
+    fn negation(self) -> Option<Rule> {
+        match self {
+            Clause::At(col, thing) => Some(Rule::NotAt(col, thing)),
+            Clause::Near(thing1, thing2) => Some(Rule::NotNear(thing1, thing2)),
+            Clause::Under(thing1, thing2) => Some(Rule::NotUnder(thing1, thing2)),
+            Clause::LeftOf(..) => None,
+        }
     }
 }
 
-impl fmt::Display for Rule {
+impl fmt::Display for Clause {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Rule::Near(thing1, thing2) => write!(f, "{} is near to {}", thing1, thing2),
-            Rule::Direction(thing1, thing2) => {
-                write!(f, "{} is from the left of {}", thing1, thing2)
-            }
-            Rule::Open(col, thing) => write!(f, "{} is at column {}", thing, col + 1),
-            Rule::Under(thing1, thing2) => write!(f, "{} is the same column as {}", thing1, thing2),
-            Rule::Between(thing1, thing2, thing3) => {
-                write!(f, "{} is between {} and {}", thing2, thing1, thing3)
-            }
-        }
+        write!(f, "{}", self.rule())
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Junction {
+    Inclusive, // at least one clause holds
+    Exclusive, // exactly one clause holds
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum Rule {
+    Near(Thing, Thing),
+    Direction(Thing, Thing),
+    Open(u8, Thing), // column
+    Under(Thing, Thing),
+    Between(Thing, Thing, Thing),
+    NotNear(Thing, Thing),
+    NotUnder(Thing, Thing),
+    NotAt(u8, Thing), // column
+    LeftOf(Thing, Thing),
+    Distance(Thing, Thing, u8), // columns apart
+    Either(Clause, Clause, Junction),
+    Above(Thing, Thing), // in the house right above
+    SameRow(Thing, Thing),
+}
+
+/// Rules without their things, in the order `generate_rule` picks from.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum RuleKind {
+    Near,
+    Open,
+    Under,
+    Direction,
+    Between,
+    NotNear,
+    NotUnder,
+    NotAt,
+    LeftOf,
+    Distance,
+    Either,
+    Above,
+    SameRow,
+}
+
+impl RuleKind {
+    pub const ALL: [RuleKind; 13] = [
+        RuleKind::Near,
+        RuleKind::Open,
+        RuleKind::Under,
+        RuleKind::Direction,
+        RuleKind::Between,
+        RuleKind::NotNear,
+        RuleKind::NotUnder,
+        RuleKind::NotAt,
+        RuleKind::LeftOf,
+        RuleKind::Distance,
+        RuleKind::Either,
+        RuleKind::Above,
+        RuleKind::SameRow,
+    ];
+}
+
+/// How often every kind of rule is generated, relative to the others.
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct RuleMix([u32; RuleKind::ALL.len()]);
+
+impl RuleMix {
+    pub fn weight(&self, kind: RuleKind) -> u32 {
+        self.0[kind as usize]
+    }
+
+    pub fn with_weight(mut self, kind: RuleKind, weight: u32) -> Self {
+        self.0[kind as usize] = weight;
+        self
+    }
+}
+
+impl Default for RuleMix {
+    fn default() -> Self {
+        RuleMix([4, 1, 2, 4, 3, 2, 1, 1, 2, 2, 1, 2, 2])
+    }
+}
+
+impl Rule {
+    pub fn kind(self) -> RuleKind {
+        match self {
+            Rule::Near(..) => RuleKind::Near,
+            Rule::Direction(..) => RuleKind::Direction,
+            Rule::Open(..) => RuleKind::Open,
+            Rule::Under(..) => RuleKind::Under,
+            Rule::Between(..) => RuleKind::Between,
+            Rule::NotNear(..) => RuleKind::NotNear,
+            Rule::NotUnder(..) => RuleKind::NotUnder,
+            Rule::NotAt(..) => RuleKind::NotAt,
+            Rule::LeftOf(..) => RuleKind::LeftOf,
+            Rule::Distance(..) => RuleKind::Distance,
+            Rule::Either(..) => RuleKind::Either,
+            Rule::Above(..) => RuleKind::Above,
+            Rule::SameRow(..) => RuleKind::SameRow,
+        }
+    }
+
+    pub fn is_non_trivial(self) -> bool {
+        !matches!(self, Rule::Open(..))
+    }
+
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Rule::Under(..) | Rule::NotUnder(..))
+    }
+
+    pub fn is_satisfied_by(self, puzzle: &SolvedPuzzle) -> bool {
+        let size = puzzle.size();
+        let col = |thing| puzzle.find(thing);
+        let next = |thing| size.shift(col(thing), 1);
+        match self {
+            Rule::Near(thing1, thing2) => size.distance(col(thing1), col(thing2)) == 1,
+            Rule::Direction(thing1, thing2) => col(thing1) < col(thing2),
+            Rule::Open(c, thing) => col(thing) == c,
+            Rule::Under(thing1, thing2) => col(thing1) == col(thing2),
+            Rule::Between(thing1, thing2, thing3) => {
+                let (col1, col2, col3) = (col(thing1), col(thing2), col(thing3));
+                (next(thing1) == Some(col2) && next(thing2) == Some(col3))
+                    || (next(thing3) == Some(col2) && next(thing2) == Some(col1))
+            }
+            Rule::NotNear(thing1, thing2) => size.distance(col(thing1), col(thing2)) != 1,
+            Rule::NotUnder(thing1, thing2) => col(thing1) != col(thing2),
+            Rule::NotAt(c, thing) => col(thing) != c,
+            Rule::LeftOf(thing1, thing2) => next(thing1) == Some(col(thing2)),
+            Rule::Distance(thing1, thing2, distance) => {
+                size.distance(col(thing1), col(thing2)) == distance
+            }
+            Rule::Either(clause1, clause2, junction) => {
+                let holds1 = clause1.rule().is_satisfied_by(puzzle);
+                let holds2 = clause2.rule().is_satisfied_by(puzzle);
+                match junction {
+                    Junction::Inclusive => holds1 || holds2,
+                    Junction::Exclusive => holds1 != holds2,
+                }
+            }
+            Rule::Above(thing1, thing2) => {
+                size.shift_vertically(col(thing1), 1) == Some(col(thing2))
+            }
+            Rule::SameRow(thing1, thing2) => {
+                col(thing1) / size.width() == col(thing2) / size.width()
+            }
+        }
+    }
+
+    /// Whether every thing and column of the rule exists in a puzzle of the size.
+    /// Only a line of houses has a left and a right end for `Direction`.
+    pub fn fits(self, size: PuzzleSize) -> bool {
+        let columns_fit = match self {
+            Rule::Open(col, _) | Rule::NotAt(col, _) => col < size.values,
+            Rule::Direction(..) => size.topology == Topology::Line,
+            Rule::Either(clause1, clause2, _) => {
+                return clause1.rule().fits(size) && clause2.rule().fits(size)
+            }
+            _ => true,
+        };
+        columns_fit
+            && self
+                .things()
+                .iter()
+                .all(|thing| thing.row.0 < size.kinds && thing.value.0 < size.values)
+    }
+
+    /// Every thing the rule mentions.
+    pub fn things(self) -> Vec<Thing> {
+        match self {
+            Rule::Open(_, thing) | Rule::NotAt(_, thing) => vec![thing],
+            Rule::Between(thing1, thing2, thing3) => vec![thing1, thing2, thing3],
+            Rule::Either(clause1, clause2, _) => {
+                let mut things = clause1.rule().things();
+                things.extend(clause2.rule().things());
+                things
+            }
+            _ => {
+                let (thing1, thing2) = self.binary_things().unwrap();
+                vec![thing1, thing2]
+            }
+        }
+    }
+
+    /// Both things of a rule which relates exactly two of them.
+    fn binary_things(self) -> Option<(Thing, Thing)> {
+        match self {
+            Rule::Near(thing1, thing2)
+            | Rule::Direction(thing1, thing2)
+            | Rule::Under(thing1, thing2)
+            | Rule::NotNear(thing1, thing2)
+            | Rule::NotUnder(thing1, thing2)
+            | Rule::LeftOf(thing1, thing2)
+            | Rule::Distance(thing1, thing2, _)
+            | Rule::Above(thing1, thing2)
+            | Rule::SameRow(thing1, thing2) => Some((thing1, thing2)),
+            _ => None,
+        }
+    }
+
+    /// Whether a binary rule holds for its things placed at `col1` and `col2`.
+    fn relates(self, size: PuzzleSize, col1: u8, col2: u8) -> bool {
+        match self {
+            Rule::Near(..) => size.distance(col1, col2) == 1,
+            Rule::Direction(..) => col1 < col2,
+            Rule::Under(..) => col1 == col2,
+            Rule::NotNear(..) => size.distance(col1, col2) != 1,
+            Rule::NotUnder(..) => col1 != col2,
+            Rule::LeftOf(..) => size.shift(col1, 1) == Some(col2),
+            Rule::Distance(_, _, distance) => size.distance(col1, col2) == distance,
+            Rule::Above(..) => size.shift_vertically(col1, 1) == Some(col2),
+            Rule::SameRow(..) => col1 / size.width() == col2 / size.width(),
+            _ => true,
+        }
+    }
+}
+
+fn generate_near_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let (col1, col2) = gen_houses_apart(rng, size, 1);
+
+    let thing1 = puzzle.get(Kind(row1), col1);
+    let thing2 = puzzle.get(Kind(row2), col2);
+
+    if rng.gen() {
+        Rule::Near(thing1, thing2)
+    } else {
+        Rule::Near(thing2, thing1)
+    }
+}
+
+fn generate_direction_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let (col1, col2) = gen_two(rng, size.values);
+
+    let thing1 = puzzle.get(Kind(row1), col1);
+    let thing2 = puzzle.get(Kind(row2), col2);
+    Rule::Direction(thing1, thing2)
+}
+
+fn generate_open_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row = rng.gen_range(0..size.kinds);
+    let col = rng.gen_range(0..size.values);
+
+    let thing = puzzle.get(Kind(row), col);
+    Rule::Open(col, thing)
+}
+
+fn generate_under_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let col: u8 = rng.gen_range(0..size.values);
+    let (row1, row2) = gen_two(rng, size.kinds);
+
+    let thing1 = puzzle.get(Kind(row1), col);
+    let thing2 = puzzle.get(Kind(row2), col);
+    Rule::Under(thing1, thing2)
+}
+
+fn generate_between_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let row3 = rng.gen_range(0..size.kinds);
+    let (col1, col3) = gen_columns_apart(rng, size, 2);
+    let col2 = size.shift(col1, 1).unwrap();
+
+    let thing1 = puzzle.get(Kind(row1), col1);
+    let thing2 = puzzle.get(Kind(row2), col2);
+    let thing3 = puzzle.get(Kind(row3), col3);
+
+    if rng.gen() {
+        Rule::Between(thing1, thing2, thing3)
+    } else {
+        Rule::Between(thing3, thing2, thing1)
+    }
+}
+
+fn generate_not_near_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let (thing1, thing2) = retry(
+        || (random_thing(rng, size), random_thing(rng, size)),
+        |(thing1, thing2)| {
+            thing1 != thing2 && size.distance(puzzle.find(*thing1), puzzle.find(*thing2)) != 1
+        },
+    );
+    Rule::NotNear(thing1, thing2)
+}
+
+fn generate_not_under_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let (col1, col2) = gen_two(rng, size.values);
+    let (row1, row2) = gen_two(rng, size.kinds);
+
+    let thing1 = puzzle.get(Kind(row1), col1);
+    let thing2 = puzzle.get(Kind(row2), col2);
+    if rng.gen() {
+        Rule::NotUnder(thing1, thing2)
+    } else {
+        Rule::NotUnder(thing2, thing1)
+    }
+}
+
+fn generate_not_at_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row = rng.gen_range(0..size.kinds);
+    let col = rng.gen_range(0..size.values);
+    let thing_col = retry(
+        || rng.gen_range(0..size.values),
+        |thing_col| *thing_col != col,
+    );
+
+    let thing = puzzle.get(Kind(row), thing_col);
+    Rule::NotAt(col, thing)
+}
+
+fn generate_left_of_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let (col1, col2) = gen_columns_apart(rng, size, 1);
+
+    let thing1 = puzzle.get(Kind(row1), col1);
+    let thing2 = puzzle.get(Kind(row2), col2);
+    Rule::LeftOf(thing1, thing2)
+}
+
+fn generate_distance_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let distance = rng.gen_range(2..=size.max_distance());
+    let (col1, col2) = gen_houses_apart(rng, size, distance);
+
+    let thing1 = puzzle.get(Kind(row1), col1);
+    let thing2 = puzzle.get(Kind(row2), col2);
+    if rng.gen() {
+        Rule::Distance(thing1, thing2, distance)
+    } else {
+        Rule::Distance(thing2, thing1, distance)
+    }
+}
+
+fn generate_clause(rng: &mut impl Rng, puzzle: &SolvedPuzzle, holds: bool) -> Clause {
+    let size = puzzle.size();
+    retry(
+        || {
+            let thing1 = random_thing(rng, size);
+            let thing2 = random_thing(rng, size);
+            match rng.gen_range(0..4) {
+                0 => Some(Clause::At(rng.gen_range(0..size.values), thing1)),
+                1 if thing1 != thing2 => Some(Clause::Near(thing1, thing2)),
+                2 if thing1.row != thing2.row => Some(Clause::Under(thing1, thing2)),
+                3 if thing1 != thing2 => Some(Clause::LeftOf(thing1, thing2)),
+                _ => None,
+            }
+        },
+        |clause| clause.is_some_and(|clause| clause.rule().is_satisfied_by(puzzle) == holds),
+    )
+    .unwrap()
+}
+
+fn generate_either_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let true_clause = generate_clause(rng, puzzle, true);
+    let false_clause = generate_clause(rng, puzzle, false);
+    let junction = if rng.gen() {
+        Junction::Inclusive
+    } else {
+        Junction::Exclusive
+    };
+    if rng.gen() {
+        Rule::Either(true_clause, false_clause, junction)
+    } else {
+        Rule::Either(false_clause, true_clause, junction)
+    }
+}
+
+fn generate_above_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let col = rng.gen_range(0..(size.values - size.width()));
+
+    let thing1 = puzzle.get(Kind(row1), col);
+    let thing2 = puzzle.get(Kind(row2), col + size.width());
+    Rule::Above(thing1, thing2)
+}
+
+fn generate_same_row_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    let size = puzzle.size();
+    let row1 = rng.gen_range(0..size.kinds);
+    let row2 = rng.gen_range(0..size.kinds);
+    let row_start = rng.gen_range(0..size.grid_rows()) * size.width();
+    let (x1, x2) = gen_two(rng, size.width());
+
+    let thing1 = puzzle.get(Kind(row1), row_start + x1);
+    let thing2 = puzzle.get(Kind(row2), row_start + x2);
+    if rng.gen() {
+        Rule::SameRow(thing1, thing2)
+    } else {
+        Rule::SameRow(thing2, thing1)
+    }
+}
+
+pub fn generate_rule(rng: &mut impl Rng, puzzle: &SolvedPuzzle) -> Rule {
+    generate_rule_with_mix(rng, puzzle, &RuleMix::default()).unwrap()
+}
+
+/// A random rule true for the puzzle, with kinds picked according to `mix`.
+/// Returns `None` if the mix leaves no kind that fits the puzzle size.
+pub fn generate_rule_with_mix(
+    rng: &mut impl Rng,
+    puzzle: &SolvedPuzzle,
+    mix: &RuleMix,
+) -> Option<Rule> {
+    let size = puzzle.size();
+    let horizontal = |min_values: u8, weight: u32| {
+        if size.width() >= min_values {
+            weight
+        } else {
+            0
+        }
+    };
+    let vertical = |weight: u32| if size.kinds >= 2 { weight } else { 0 };
+    // there is no left and right at a round table
+    let linear = |weight: u32| match size.topology {
+        Topology::Line => weight,
+        _ => 0,
+    };
+    let distant = |min_distance: u8, weight: u32| {
+        if size.max_distance() >= min_distance {
+            weight
+        } else {
+            0
+        }
+    };
+    let grid = |weight: u32| if size.grid_rows() >= 2 { weight } else { 0 };
+    let weight = |kind| mix.weight(kind);
+    let weights = [
+        distant(1, weight(RuleKind::Near)),
+        weight(RuleKind::Open),
+        vertical(weight(RuleKind::Under)),
+        linear(horizontal(2, weight(RuleKind::Direction))),
+        horizontal(3, weight(RuleKind::Between)),
+        distant(2, weight(RuleKind::NotNear)),
+        vertical(horizontal(2, weight(RuleKind::NotUnder))),
+        horizontal(2, weight(RuleKind::NotAt)),
+        horizontal(2, weight(RuleKind::LeftOf)),
+        distant(2, weight(RuleKind::Distance)),
+        horizontal(2, weight(RuleKind::Either)),
+        grid(weight(RuleKind::Above)),
+        grid(horizontal(2, weight(RuleKind::SameRow))),
+    ];
+    let dist = WeightedIndex::new(weights).ok()?;
+    Some(match RuleKind::ALL[dist.sample(rng)] {
+        RuleKind::Near => generate_near_rule(rng, puzzle),
+        RuleKind::Open => generate_open_rule(rng, puzzle),
+        RuleKind::Under => generate_under_rule(rng, puzzle),
+        RuleKind::Direction => generate_direction_rule(rng, puzzle),
+        RuleKind::Between => generate_between_rule(rng, puzzle),
+        RuleKind::NotNear => generate_not_near_rule(rng, puzzle),
+        RuleKind::NotUnder => generate_not_under_rule(rng, puzzle),
+        RuleKind::NotAt => generate_not_at_rule(rng, puzzle),
+        RuleKind::LeftOf => generate_left_of_rule(rng, puzzle),
+        RuleKind::Distance => generate_distance_rule(rng, puzzle),
+        RuleKind::Either => generate_either_rule(rng, puzzle),
+        RuleKind::Above => generate_above_rule(rng, puzzle),
+        RuleKind::SameRow => generate_same_row_rule(rng, puzzle),
+    })
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Rule::Near(thing1, thing2) => write!(f, "{} is near to {}", thing1, thing2),
+            Rule::Direction(thing1, thing2) => {
+                write!(f, "{} is from the left of {}", thing1, thing2)
+            }
+            Rule::Open(col, thing) => write!(f, "{} is at column {}", thing, col + 1),
+            Rule::Under(thing1, thing2) => write!(f, "{} is the same column as {}", thing1, thing2),
+            Rule::Between(thing1, thing2, thing3) => {
+                write!(f, "{} is between {} and {}", thing2, thing1, thing3)
+            }
+            Rule::NotNear(thing1, thing2) => write!(f, "{} is not near to {}", thing1, thing2),
+            Rule::NotUnder(thing1, thing2) => {
+                write!(f, "{} is not the same column as {}", thing1, thing2)
+            }
+            Rule::NotAt(col, thing) => write!(f, "{} is not at column {}", thing, col + 1),
+            Rule::LeftOf(thing1, thing2) => {
+                write!(f, "{} is immediately to the left of {}", thing1, thing2)
+            }
+            Rule::Distance(thing1, thing2, distance) => {
+                write!(f, "{} is {} columns away from {}", thing1, distance, thing2)
+            }
+            Rule::Either(clause1, clause2, Junction::Inclusive) => {
+                write!(f, "{}, or {}", clause1, clause2)
+            }
+            Rule::Either(clause1, clause2, Junction::Exclusive) => {
+                write!(f, "either {}, or {}, but not both", clause1, clause2)
+            }
+            Rule::Above(thing1, thing2) => write!(f, "{} is right above {}", thing1, thing2),
+            Rule::SameRow(thing1, thing2) => {
+                write!(f, "{} is in the same row as {}", thing1, thing2)
+            }
+        }
     }
 }
 
 pub fn apply(pos: &Possibilities, rule: &Rule) -> Possibilities {
+    let mut board = Board {
+        pos: pos.clone(),
+        trace: None,
+    };
+    board.converge(|board| apply_to_board(board, rule));
+    board.pos
+}
+
+pub fn apply_traced(pos: &Possibilities, rule: &Rule) -> (Possibilities, Vec<Elimination>) {
+    let mut trace = Vec::new();
+    let mut board = Board {
+        pos: pos.clone(),
+        trace: Some(&mut trace),
+    };
+    board.converge(|board| apply_to_board(board, rule));
+    (board.pos, trace)
+}
+
+pub(crate) fn revise(pos: &Possibilities, rule: Rule) -> Possibilities {
+    let Some((thing1, thing2)) = rule.binary_things() else {
+        return pos.clone();
+    };
+    let size = pos.size;
+    let compatible = |col1: u8, col2: u8| {
+        let placeable = if thing1 == thing2 {
+            col1 == col2
+        } else {
+            // a kind has only one value in a column
+            thing1.row != thing2.row || col1 != col2
+        };
+        placeable && rule.relates(size, col1, col2)
+    };
+    let mut pos = pos.clone();
+    for col in 0..size.values {
+        let has_partner2 =
+            (0..size.values).any(|other| pos.is_possible(other, thing2) && compatible(col, other));
+        if !has_partner2 && pos.is_possible(col, thing1) {
+            pos.exclude_in_place(col, thing1.row, thing1.value);
+        }
+        let has_partner1 =
+            (0..size.values).any(|other| pos.is_possible(other, thing1) && compatible(other, col));
+        if !has_partner1 && pos.is_possible(col, thing2) {
+            pos.exclude_in_place(col, thing2.row, thing2.value);
+        }
+    }
+    pos
+}
+
+/// Removes every candidate which has no supporting placement of the other
+/// thing of some binary rule, until nothing changes. Unlike `apply`, it
+/// relies only on the relation itself, so it also catches eliminations the
+/// rules propagate lazily, e.g. `NotNear` before either thing is placed.
+pub fn arc_consistency(pos: &Possibilities, rules: &[Rule]) -> Possibilities {
+    converge(pos.clone(), |pos| {
+        rules.iter().fold(pos, |pos, rule| revise(&pos, *rule))
+    })
+}
+
+fn apply_to_board(board: &mut Board, rule: &Rule) {
     match *rule {
         Rule::Near(thing1, thing2) => {
             fn is_applicable_to_col(
@@ -464,64 +1462,145 @@ pub fn apply(pos: &Possibilities, rule: &Rule) -> Possibilities {
                 thing: Thing,
                 neighbour: Thing,
             ) -> bool {
-                let has_left = if col == 0 {
-                    false
-                } else {
-                    pos.is_possible(col - 1, neighbour)
-                };
+                let has_neighbour = pos
+                    .size
+                    .at_distance(col, 1)
+                    .any(|near| pos.is_possible(near, neighbour));
 
-                let has_right = if col + 1 == pos.size.values {
-                    false
-                } else {
-                    pos.is_possible(col + 1, neighbour)
-                };
-
-                !has_right && !has_left && pos.is_possible(col, thing)
+                !has_neighbour && pos.is_possible(col, thing)
             }
-            converge(pos.clone(), |mut pos| {
-                for col in 0..pos.size.values {
-                    if is_applicable_to_col(&pos, col, thing1, thing2) {
-                        pos = pos.exclude(col, thing1.row, thing1.value);
-                    }
-                    if is_applicable_to_col(&pos, col, thing2, thing1) {
-                        pos = pos.exclude(col, thing2.row, thing2.value);
-                    }
+            for col in 0..board.pos.size.values {
+                if is_applicable_to_col(&board.pos, col, thing1, thing2) {
+                    board.exclude(col, thing1);
                 }
-                pos
-            })
+                if is_applicable_to_col(&board.pos, col, thing2, thing1) {
+                    board.exclude(col, thing2);
+                }
+            }
         }
         Rule::Direction(thing1, thing2) => {
-            let mut new_pos = pos.clone();
-            for col in 0..pos.size.values {
-                if new_pos.is_possible(col, thing2) {
-                    new_pos = new_pos.exclude(col, thing2.row, thing2.value);
-                }
-                if new_pos.is_possible(col, thing1) {
+            for col in 0..board.pos.size.values {
+                board.exclude(col, thing2);
+                if board.pos.is_possible(col, thing1) {
                     break;
                 }
             }
-            for col in (0..pos.size.values).rev() {
-                if new_pos.is_possible(col, thing1) {
-                    new_pos = new_pos.exclude(col, thing1.row, thing1.value);
-                }
-                if new_pos.is_possible(col, thing2) {
+            for col in (0..board.pos.size.values).rev() {
+                board.exclude(col, thing1);
+                if board.pos.is_possible(col, thing2) {
                     break;
                 }
             }
-            new_pos
         }
-        Rule::Open(col, thing) => pos.set(col, thing.row, thing.value),
+        Rule::Open(col, thing) => board.set(col, thing),
         Rule::Under(thing1, thing2) => {
-            let mut new_pos = pos.clone();
-            for col in 0..pos.size.values {
-                if !new_pos.is_possible(col, thing1) {
-                    new_pos = new_pos.exclude(col, thing2.row, thing2.value);
+            for col in 0..board.pos.size.values {
+                if !board.pos.is_possible(col, thing1) {
+                    board.exclude(col, thing2);
                 }
-                if !new_pos.is_possible(col, thing2) {
-                    new_pos = new_pos.exclude(col, thing1.row, thing1.value);
+                if !board.pos.is_possible(col, thing2) {
+                    board.exclude(col, thing1);
+                }
+            }
+        }
+        Rule::NotNear(thing1, thing2) => {
+            for col in 0..board.pos.size.values {
+                for (thing, other) in [(thing1, thing2), (thing2, thing1)] {
+                    if board.pos.is_at(col, thing) {
+                        for near in board.pos.size.at_distance(col, 1) {
+                            board.exclude(near, other);
+                        }
+                    }
+                }
+            }
+        }
+        Rule::NotUnder(thing1, thing2) => {
+            for col in 0..board.pos.size.values {
+                if board.pos.is_at(col, thing1) {
+                    board.exclude(col, thing2);
+                }
+                if board.pos.is_at(col, thing2) {
+                    board.exclude(col, thing1);
+                }
+            }
+        }
+        Rule::NotAt(col, thing) => board.exclude(col, thing),
+        Rule::LeftOf(thing1, thing2) => {
+            for col in 0..board.pos.size.values {
+                let size = board.pos.size;
+                let has_right = size
+                    .shift(col, 1)
+                    .is_some_and(|right| board.pos.is_possible(right, thing2));
+                if !has_right {
+                    board.exclude(col, thing1);
+                }
+                let has_left = size
+                    .shift(col, -1)
+                    .is_some_and(|left| board.pos.is_possible(left, thing1));
+                if !has_left {
+                    board.exclude(col, thing2);
+                }
+            }
+        }
+        Rule::Distance(thing1, thing2, distance) => {
+            fn has_partner(pos: &Possibilities, col: u8, partner: Thing, distance: u8) -> bool {
+                pos.size
+                    .at_distance(col, distance)
+                    .any(|other| pos.is_possible(other, partner))
+            }
+            for col in 0..board.pos.size.values {
+                if !has_partner(&board.pos, col, thing2, distance) {
+                    board.exclude(col, thing1);
+                }
+                if !has_partner(&board.pos, col, thing1, distance) {
+                    board.exclude(col, thing2);
+                }
+            }
+        }
+        Rule::Either(clause1, clause2, junction) => {
+            let branch = |clause: Clause, other: Clause| {
+                let pos = apply(&board.pos, &clause.rule());
+                match other.negation() {
+                    Some(negation) if junction == Junction::Exclusive => apply(&pos, &negation),
+                    _ => pos,
+                }
+            };
+            let pos1 = branch(clause1, clause2);
+            let pos2 = branch(clause2, clause1);
+            let target = match (pos1.is_consistent(), pos2.is_consistent()) {
+                (true, true) => pos1.unite(&pos2),
+                (false, true) => pos2,
+                _ => pos1,
+            };
+            board.narrow_to(&target);
+        }
+        Rule::Above(thing1, thing2) => {
+            for col in 0..board.pos.size.values {
+                let size = board.pos.size;
+                let has_below = size
+                    .shift_vertically(col, 1)
+                    .is_some_and(|below| board.pos.is_possible(below, thing2));
+                if !has_below {
+                    board.exclude(col, thing1);
+                }
+                let has_above = size
+                    .shift_vertically(col, -1)
+                    .is_some_and(|above| board.pos.is_possible(above, thing1));
+                if !has_above {
+                    board.exclude(col, thing2);
+                }
+            }
+        }
+        Rule::SameRow(thing1, thing2) => {
+            let width = board.pos.size.width();
+            for col in 0..board.pos.size.values {
+                let row_start = col - col % width;
+                for (thing, other) in [(thing1, thing2), (thing2, thing1)] {
+                    if !(row_start..row_start + width).any(|c| board.pos.is_possible(c, other)) {
+                        board.exclude(col, thing);
+                    }
                 }
             }
-            new_pos
         }
         Rule::Between(thing1, thing2, thing3) => {
             fn check_middle_thing(
@@ -531,11 +1610,13 @@ pub fn apply(pos: &Possibilities, rule: &Rule) -> Possibilities {
                 thing2: Thing,
                 thing3: Thing,
             ) -> bool {
-                col > 0
-                    && col < pos.size.values - 1
-                    && pos.is_possible(col, thing2)
-                    && ((pos.is_possible(col - 1, thing1) && pos.is_possible(col + 1, thing3))
-                        || (pos.is_possible(col - 1, thing3) && pos.is_possible(col + 1, thing1)))
+                let (Some(left), Some(right)) = (pos.size.shift(col, -1), pos.size.shift(col, 1))
+                else {
+                    return false;
+                };
+                pos.is_possible(col, thing2)
+                    && ((pos.is_possible(left, thing1) && pos.is_possible(right, thing3))
+                        || (pos.is_possible(left, thing3) && pos.is_possible(right, thing1)))
             }
 
             fn check_side_thing(
@@ -545,37 +1626,29 @@ pub fn apply(pos: &Possibilities, rule: &Rule) -> Possibilities {
                 thing2: Thing,
                 thing3: Thing,
             ) -> bool {
-                if pos.is_possible(col, thing3) {
-                    let left_possible = if col < 2 {
-                        false
-                    } else {
-                        pos.is_possible(col - 1, thing2) && pos.is_possible(col - 2, thing1)
-                    };
-                    let right_possible = if col >= pos.size.values - 2 {
-                        false
-                    } else {
-                        pos.is_possible(col + 1, thing2) && pos.is_possible(col + 2, thing1)
-                    };
-                    left_possible || right_possible
-                } else {
-                    false
-                }
+                let side_possible = |direction: i8| match (
+                    pos.size.shift(col, direction),
+                    pos.size.shift(col, 2 * direction),
+                ) {
+                    (Some(middle), Some(far)) => {
+                        pos.is_possible(middle, thing2) && pos.is_possible(far, thing1)
+                    }
+                    _ => false,
+                };
+                pos.is_possible(col, thing3) && (side_possible(-1) || side_possible(1))
             }
 
-            converge(pos.clone(), |mut pos| {
-                for col in 0..pos.size.values {
-                    if !check_middle_thing(&pos, col, thing1, thing2, thing3) {
-                        pos = pos.exclude(col, thing2.row, thing2.value);
-                    }
-                    if !check_side_thing(&pos, col, thing1, thing2, thing3) {
-                        pos = pos.exclude(col, thing3.row, thing3.value);
-                    }
-                    if !check_side_thing(&pos, col, thing3, thing2, thing1) {
-                        pos = pos.exclude(col, thing1.row, thing1.value);
-                    }
+            for col in 0..board.pos.size.values {
+                if !check_middle_thing(&board.pos, col, thing1, thing2, thing3) {
+                    board.exclude(col, thing2);
                 }
-                pos
-            })
+                if !check_side_thing(&board.pos, col, thing1, thing2, thing3) {
+                    board.exclude(col, thing3);
+                }
+                if !check_side_thing(&board.pos, col, thing3, thing2, thing1) {
+                    board.exclude(col, thing1);
+                }
+            }
         }
     }
 }
@@ -583,15 +1656,494 @@ pub fn apply(pos: &Possibilities, rule: &Rule) -> Possibilities {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::puzzle_gen::puzzle_rng;
+    use proptest::prelude::*;
+    use rand::Rng;
 
     #[test]
     fn test_eq_possibilities() {
         let size = PuzzleSize {
             kinds: 6,
             values: 6,
+            topology: Topology::Line,
         };
         let p1 = Possibilities::new(size).exclude(0, Kind(0), Value(0));
         let p2 = Possibilities::new(size).exclude(0, Kind(0), Value(0));
         assert_eq!(p1, p2);
     }
+
+    #[test]
+    fn test_possibilities_serialization() {
+        // saved games store every row as a list of candidate bit masks
+        let json = r#"{"size":{"kinds":2,"values":3},"rows":[[7,7,7],[1,6,6]]}"#;
+        let pos: Possibilities = serde_json::from_str(json).unwrap();
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 3,
+            topology: Topology::Line,
+        };
+        assert_eq!(pos, Possibilities::new(size).set(0, Kind(1), Value(0)));
+        let round_trip = serde_json::to_string(&pos).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Possibilities>(&round_trip).unwrap(),
+            pos
+        );
+
+        let json = r#"{"size":{"kinds":2,"values":3},"rows":[[7,7,7]]}"#;
+        assert!(serde_json::from_str::<Possibilities>(json).is_err());
+    }
+
+    #[test]
+    fn test_rectangular_size() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 7,
+            topology: Topology::Line,
+        };
+        assert_eq!(size.iter_kinds().count(), 4);
+        assert_eq!(size.iter_values().count(), 7);
+
+        let puzzle = SolvedPuzzle::random(size, &mut rand::thread_rng());
+        for kind in size.iter_kinds() {
+            let mut values: Vec<Value> = (0..size.values)
+                .map(|col| puzzle.get_value(kind, col))
+                .collect();
+            values.sort_by_key(|value| value.0);
+            assert_eq!(values, size.iter_values().collect::<Vec<_>>());
+        }
+
+        let pos = Possibilities::new(size);
+        assert!(pos.is_valid(&puzzle));
+        assert_eq!(pos.get_possible(6, Kind(3)).size(), 7);
+    }
+
+    #[test]
+    fn test_generated_rules_hold() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let mut rng = rand::thread_rng();
+        let puzzle = SolvedPuzzle::random(size, &mut rng);
+        for _ in 0..500 {
+            let rule = generate_rule(&mut rng, &puzzle);
+            assert!(rule.is_satisfied_by(&puzzle), "{}", rule);
+            assert!(apply(&Possibilities::new(size), &rule).is_valid(&puzzle));
+        }
+    }
+
+    #[test]
+    fn test_generate_rule_with_mix() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let mut rng = rand::thread_rng();
+        let puzzle = SolvedPuzzle::random(size, &mut rng);
+        let only = |kind| {
+            RuleKind::ALL
+                .into_iter()
+                .fold(RuleMix::default(), |mix, other| {
+                    mix.with_weight(other, u32::from(other == kind))
+                })
+        };
+        for kind in RuleKind::ALL {
+            let rule = generate_rule_with_mix(&mut rng, &puzzle, &only(kind));
+            match kind {
+                // a line has no rows of houses
+                RuleKind::Above | RuleKind::SameRow => assert_eq!(rule, None),
+                _ => {
+                    let rule = rule.unwrap();
+                    assert_eq!(rule.kind(), kind);
+                    assert!(rule.is_satisfied_by(&puzzle), "{}", rule);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_negative_rules() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 4,
+            topology: Topology::Line,
+        };
+        let a = Thing {
+            row: Kind(0),
+            value: Value(0),
+        };
+        let b = Thing {
+            row: Kind(1),
+            value: Value(0),
+        };
+        let pos = Possibilities::new(size).set(1, a.row, a.value);
+
+        let not_near = apply(&pos, &Rule::NotNear(a, b));
+        assert!(!not_near.is_possible(0, b));
+        assert!(not_near.is_possible(1, b));
+        assert!(!not_near.is_possible(2, b));
+
+        let not_under = apply(&pos, &Rule::NotUnder(b, a));
+        assert!(!not_under.is_possible(1, b));
+        assert!(not_under.is_possible(2, b));
+
+        let not_at = apply(&pos, &Rule::NotAt(3, b));
+        assert!(!not_at.is_possible(3, b));
+    }
+
+    #[test]
+    fn test_either_rule() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let a = Thing {
+            row: Kind(0),
+            value: Value(0),
+        };
+        let b = Thing {
+            row: Kind(1),
+            value: Value(0),
+        };
+        let pos = Possibilities::new(size);
+
+        let rule = Rule::Either(Clause::At(0, a), Clause::At(4, a), Junction::Inclusive);
+        let either = apply(&pos, &rule);
+        assert!(either.is_possible(0, a));
+        assert!(!either.is_possible(2, a));
+        assert!(either.is_possible(4, a));
+
+        // the first clause is impossible, so the second one must hold
+        let pos = pos.set(1, b.row, b.value);
+        let rule = Rule::Either(Clause::Under(a, b), Clause::At(3, a), Junction::Exclusive);
+        let pos = pos.exclude(1, a.row, a.value);
+        assert_eq!(apply(&pos, &rule).get_defined(3, a.row), Some(a.value));
+    }
+
+    #[test]
+    fn test_ring_rules() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 6,
+            topology: Topology::Ring,
+        };
+        assert_eq!(size.shift(5, 1), Some(0));
+        assert_eq!(size.shift(0, -2), Some(4));
+        assert_eq!(size.distance(0, 5), 1);
+        assert_eq!(size.max_distance(), 3);
+
+        let a = Thing {
+            row: Kind(0),
+            value: Value(0),
+        };
+        let b = Thing {
+            row: Kind(1),
+            value: Value(0),
+        };
+        let pos = Possibilities::new(size).set(0, a.row, a.value);
+
+        let near = apply(&pos, &Rule::Near(a, b));
+        assert!(near.is_possible(1, b));
+        assert!(near.is_possible(5, b));
+        assert!(!near.is_possible(2, b));
+
+        assert_eq!(
+            apply(&pos, &Rule::LeftOf(b, a)).get_defined(5, b.row),
+            Some(b.value)
+        );
+        assert_eq!(
+            apply(&pos, &Rule::Distance(a, b, 3)).get_defined(3, b.row),
+            Some(b.value)
+        );
+    }
+
+    #[test]
+    fn test_grid_rules() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 6,
+            topology: Topology::Grid(3),
+        };
+        assert_eq!(size.grid_rows(), 2);
+        assert_eq!(size.shift(2, 1), None);
+        assert_eq!(size.shift_vertically(1, 1), Some(4));
+        assert_eq!(size.distance(0, 5), 3);
+        assert_eq!(size.at_distance(4, 1).collect::<Vec<_>>(), vec![1, 3, 5]);
+
+        let a = Thing {
+            row: Kind(0),
+            value: Value(0),
+        };
+        let b = Thing {
+            row: Kind(1),
+            value: Value(0),
+        };
+        let pos = Possibilities::new(size).set(1, a.row, a.value);
+
+        let near = apply(&pos, &Rule::Near(a, b));
+        for col in [0, 2, 4] {
+            assert!(near.is_possible(col, b));
+        }
+        assert!(!near.is_possible(3, b));
+
+        assert_eq!(
+            apply(&pos, &Rule::Above(a, b)).get_defined(4, b.row),
+            Some(b.value)
+        );
+        let same_row = apply(&pos, &Rule::SameRow(b, a));
+        assert!(same_row.is_possible(2, b));
+        assert!(!same_row.is_possible(5, b));
+    }
+
+    #[test]
+    fn test_subsets() {
+        let size = PuzzleSize {
+            kinds: 1,
+            values: 6,
+            topology: Topology::Line,
+        };
+        let kind = Kind(0);
+        let mut naked = Possibilities::new(size);
+        let mut hidden = Possibilities::new(size);
+        for col in 0..2 {
+            for value in 2..6 {
+                naked = naked.exclude(col, kind, Value(value));
+            }
+        }
+        for col in 2..6 {
+            for value in 0..2 {
+                hidden = hidden.exclude(col, kind, Value(value));
+            }
+        }
+        assert!(naked.is_possible(
+            4,
+            Thing {
+                row: kind,
+                value: Value(0)
+            }
+        ));
+        assert_eq!(hidden.get_possible(0, kind).size(), 6);
+
+        let naked = naked.with_propagation(Propagation::Subsets);
+        let hidden = hidden.with_propagation(Propagation::Subsets);
+        for col in 2..6 {
+            assert_eq!(naked.get_possible(col, kind).size(), 4);
+        }
+        for col in 0..2 {
+            assert_eq!(hidden.get_possible(col, kind).size(), 2);
+        }
+    }
+
+    #[test]
+    fn test_arc_consistency() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let a = Thing {
+            row: Kind(0),
+            value: Value(0),
+        };
+        let b = Thing {
+            row: Kind(1),
+            value: Value(0),
+        };
+        let mut pos = Possibilities::new(size);
+        for col in [0, 2, 4] {
+            pos = pos.exclude(col, b.row, b.value);
+        }
+        let rules = [Rule::NotNear(a, b)];
+        assert_eq!(apply(&pos, &rules[0]), pos);
+        let arc = arc_consistency(&pos, &rules);
+        assert!(!arc.is_possible(2, a));
+        assert!(arc.is_possible(0, a));
+        assert!(arc.is_possible(4, a));
+    }
+
+    #[test]
+    fn test_distance_rules() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 5,
+            topology: Topology::Line,
+        };
+        let a = Thing {
+            row: Kind(0),
+            value: Value(0),
+        };
+        let b = Thing {
+            row: Kind(1),
+            value: Value(0),
+        };
+        let pos = Possibilities::new(size);
+
+        let left_of = apply(&pos, &Rule::LeftOf(a, b));
+        assert!(!left_of.is_possible(4, a));
+        assert!(!left_of.is_possible(0, b));
+
+        let distance = apply(&pos, &Rule::Distance(a, b, 3));
+        for col in [0, 1, 3, 4] {
+            assert!(distance.is_possible(col, a));
+        }
+        assert!(!distance.is_possible(2, a));
+        assert!(!distance.is_possible(2, b));
+
+        let pos = pos.set(1, a.row, a.value);
+        assert_eq!(
+            apply(&pos, &Rule::LeftOf(a, b)).get_defined(2, b.row),
+            Some(b.value)
+        );
+        assert_eq!(
+            apply(&pos, &Rule::Distance(a, b, 3)).get_defined(4, b.row),
+            Some(b.value)
+        );
+    }
+
+    fn arb_size() -> impl Strategy<Value = PuzzleSize> {
+        prop_oneof![
+            (1_u8..=6, 2_u8..=8).prop_map(|(kinds, values)| PuzzleSize {
+                kinds,
+                values,
+                topology: Topology::Line,
+            }),
+            (1_u8..=6, 2_u8..=8).prop_map(|(kinds, values)| PuzzleSize {
+                kinds,
+                values,
+                topology: Topology::Ring,
+            }),
+            (1_u8..=5, 2_u8..=4, 2_u8..=3).prop_map(|(kinds, width, rows)| PuzzleSize {
+                kinds,
+                values: width * rows,
+                topology: Topology::Grid(width),
+            }),
+        ]
+    }
+
+    // A solution, some rules it satisfies and a board narrowed by some of
+    // the rules and by random wrong candidates.
+    fn scenario(size: PuzzleSize, seed: u64) -> (SolvedPuzzle, Vec<Rule>, Possibilities) {
+        let mut rng = puzzle_rng(seed);
+        let puzzle = SolvedPuzzle::random(size, &mut rng);
+        let rules: Vec<Rule> = (0..30).map(|_| generate_rule(&mut rng, &puzzle)).collect();
+        let mut pos = Possibilities::new(size);
+        for rule in rules.iter().take(rng.gen_range(0..8)) {
+            pos = apply(&pos, rule);
+        }
+        for _ in 0..rng.gen_range(0..usize::from(size.kinds) * usize::from(size.values)) {
+            let thing = random_thing(&mut rng, size);
+            let col = rng.gen_range(0..size.values);
+            if puzzle.get_value(thing.row, col) != thing.value {
+                pos = pos.exclude(col, thing.row, thing.value);
+            }
+        }
+        (puzzle, rules, pos)
+    }
+
+    fn is_narrowing(before: &Possibilities, after: &Possibilities) -> bool {
+        let size = before.size();
+        size.iter_kinds().all(|kind| {
+            (0..size.values).all(|col| {
+                let cell = after.get_possible(col, kind);
+                cell.intersection(&before.get_possible(col, kind)) == cell
+            })
+        })
+    }
+
+    fn is_narrowing_row(before: &[ValueSet], after: &[ValueSet]) -> bool {
+        before
+            .iter()
+            .zip(after)
+            .all(|(before, after)| after.intersection(before) == *after)
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(200))]
+
+        #[test]
+        fn test_apply_is_sound_monotonic_and_idempotent(size in arb_size(), seed: u64) {
+            let (puzzle, rules, pos) = scenario(size, seed);
+            prop_assert!(pos.is_valid(&puzzle));
+            for rule in &rules {
+                prop_assert!(rule.is_satisfied_by(&puzzle), "{}", rule);
+                let applied = apply(&pos, rule);
+                prop_assert!(applied.is_valid(&puzzle), "{} removed the solution", rule);
+                prop_assert!(is_narrowing(&pos, &applied), "{} added candidates", rule);
+                prop_assert_eq!(apply(&applied, rule), applied.clone(), "{} is not idempotent", rule);
+
+                // a narrower board stays narrower after the rule
+                let narrower = apply(&pos, &rules[0]);
+                prop_assert!(is_narrowing(&applied, &apply(&narrower, rule)), "{} is not monotonic", rule);
+            }
+        }
+
+        #[test]
+        fn test_row_checks_are_sound_monotonic_and_idempotent(size in arb_size(), seed: u64) {
+            let (puzzle, rules, pos) = scenario(size, seed);
+            let pos = apply(&pos, &rules[0]);
+            for propagation in Propagation::ALL {
+                let checked = pos.with_propagation(propagation);
+                prop_assert!(checked.is_valid(&puzzle));
+                prop_assert!(is_narrowing(&pos, &checked));
+                for kind in size.iter_kinds() {
+                    let mut row = checked.row(kind).to_vec();
+                    check_row(&mut row, propagation);
+                    prop_assert_eq!(row.as_slice(), checked.row(kind));
+                    prop_assert!(!check_singles(&mut row));
+                }
+            }
+
+            // a row without some wrong candidates stays narrower after the checks
+            let mut rng = puzzle_rng(seed);
+            for kind in size.iter_kinds() {
+                let wide: Vec<ValueSet> = (0..size.values)
+                    .map(|col| {
+                        let mut cell = ValueSet::full(size.values);
+                        for value in cell.iter() {
+                            if value != puzzle.get_value(kind, col) && rng.gen_bool(0.3) {
+                                cell.remove(value);
+                            }
+                        }
+                        cell
+                    })
+                    .collect();
+                let narrow: Vec<ValueSet> = wide
+                    .iter()
+                    .enumerate()
+                    .map(|(col, cell)| {
+                        let mut cell = *cell;
+                        for value in cell.iter() {
+                            if value != puzzle.get_value(kind, col as u8) && rng.gen_bool(0.3) {
+                                cell.remove(value);
+                            }
+                        }
+                        cell
+                    })
+                    .collect();
+
+                let (mut wide_singles, mut narrow_singles) = (wide.clone(), narrow.clone());
+                check_singles(&mut wide_singles);
+                check_singles(&mut narrow_singles);
+                prop_assert!(is_narrowing_row(&wide_singles, &narrow_singles));
+                for propagation in Propagation::ALL {
+                    let (mut wide_checked, mut narrow_checked) = (wide.clone(), narrow.clone());
+                    check_row(&mut wide_checked, propagation);
+                    check_row(&mut narrow_checked, propagation);
+                    prop_assert!(is_narrowing_row(&wide_checked, &narrow_checked), "{}", propagation);
+                }
+            }
+        }
+
+        #[test]
+        fn test_arc_consistency_is_sound(size in arb_size(), seed: u64) {
+            let (puzzle, rules, pos) = scenario(size, seed);
+            let arc = arc_consistency(&pos, &rules);
+            prop_assert!(arc.is_valid(&puzzle));
+            prop_assert!(is_narrowing(&pos, &arc));
+            prop_assert_eq!(arc_consistency(&arc, &rules), arc);
+        }
+    }
 }
@@ -0,0 +1,164 @@
+use crate::propagation::propagate;
+use crate::rules::{Kind, Possibilities, PuzzleSize, Rule, SolvedPuzzle};
+use std::fmt;
+
+/// A rule which means nothing for the puzzle size.
+#[derive(PartialEq, Eq, Debug)]
+pub struct UnfitRule(pub Rule);
+
+impl std::error::Error for UnfitRule {}
+
+impl fmt::Display for UnfitRule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Rule \"{}\" does not fit the puzzle size", self.0)
+    }
+}
+
+fn check_rules(size: PuzzleSize, rules: &[Rule]) -> Result<(), UnfitRule> {
+    match rules.iter().find(|rule| !rule.fits(size)) {
+        Some(rule) => Err(UnfitRule(*rule)),
+        None => Ok(()),
+    }
+}
+
+// Undecided cell with the fewest candidates left.
+fn choose_cell(possibilities: &Possibilities) -> Option<(u8, Kind)> {
+    let size = possibilities.size();
+    size.iter_kinds()
+        .flat_map(|kind| (0..size.values).map(move |col| (col, kind)))
+        .filter(|(col, kind)| !possibilities.is_defined(*col, *kind))
+        .min_by_key(|(col, kind)| possibilities.get_possible(*col, *kind).size())
+}
+
+fn search(
+    possibilities: &Possibilities,
+    rules: &[Rule],
+    limit: usize,
+    solutions: &mut Vec<SolvedPuzzle>,
+) {
+    let possibilities = propagate(possibilities, rules);
+    if !possibilities.is_consistent() {
+        return;
+    }
+    let Some((col, kind)) = choose_cell(&possibilities) else {
+        if let Some(puzzle) = SolvedPuzzle::from_possibilities(&possibilities) {
+            if rules.iter().all(|rule| rule.is_satisfied_by(&puzzle)) {
+                solutions.push(puzzle);
+            }
+        }
+        return;
+    };
+    for value in possibilities.get_possible(col, kind).iter() {
+        if solutions.len() >= limit {
+            return;
+        }
+        search(
+            &possibilities.set(col, kind, value),
+            rules,
+            limit,
+            solutions,
+        );
+    }
+}
+
+/// Finds up to `limit` distinct solutions of the rules by propagation and backtracking.
+pub fn solve_limited(
+    size: PuzzleSize,
+    rules: &[Rule],
+    limit: usize,
+) -> Result<Vec<SolvedPuzzle>, UnfitRule> {
+    check_rules(size, rules)?;
+    let mut solutions = Vec::new();
+    if limit > 0 {
+        search(&Possibilities::new(size), rules, limit, &mut solutions);
+    }
+    Ok(solutions)
+}
+
+/// Finds every solution of the rules. For loosely constrained rule sets
+/// this may be a lot, so prefer `count_solutions` with a limit.
+pub fn solve_all(size: PuzzleSize, rules: &[Rule]) -> Result<Vec<SolvedPuzzle>, UnfitRule> {
+    solve_limited(size, rules, usize::MAX)
+}
+
+/// Counts solutions of the rules, stopping as soon as `limit` is reached.
+pub fn count_solutions(size: PuzzleSize, rules: &[Rule], limit: usize) -> Result<usize, UnfitRule> {
+    Ok(solve_limited(size, rules, limit)?.len())
+}
+
+pub fn has_unique_solution(size: PuzzleSize, rules: &[Rule]) -> Result<bool, UnfitRule> {
+    Ok(count_solutions(size, rules, 2)? == 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::difficulty::Difficulty;
+    use crate::puzzle_gen::generate_puzzle;
+    use crate::rules::{Thing, Topology, Value};
+
+    #[test]
+    fn test_generated_puzzle_is_unique() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 5,
+            topology: Topology::Line,
+        };
+        for difficulty in Difficulty::ALL {
+            let (puzzle, rules) = generate_puzzle(size, difficulty, 1).unwrap();
+            assert_eq!(solve_all(size, &rules).unwrap(), vec![puzzle]);
+        }
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 3,
+            topology: Topology::Line,
+        };
+        // 3! arrangements of each row
+        assert_eq!(count_solutions(size, &[], 100).unwrap(), 36);
+        assert_eq!(count_solutions(size, &[], 10).unwrap(), 10);
+        assert_eq!(count_solutions(size, &[], 0).unwrap(), 0);
+
+        let thing = |row, value| Thing {
+            row: Kind(row),
+            value: Value(value),
+        };
+        let rules = [
+            Rule::Direction(thing(0, 0), thing(0, 1)),
+            Rule::Direction(thing(0, 1), thing(0, 2)),
+        ];
+        assert_eq!(count_solutions(size, &rules, 100).unwrap(), 6);
+
+        let contradiction = [
+            Rule::Direction(thing(0, 0), thing(0, 1)),
+            Rule::Direction(thing(0, 1), thing(0, 0)),
+        ];
+        assert_eq!(count_solutions(size, &contradiction, 100).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_direction_needs_a_line() {
+        let thing = |row, value| Thing {
+            row: Kind(row),
+            value: Value(value),
+        };
+        let rules = [Rule::Direction(thing(0, 0), thing(0, 1))];
+        for topology in [Topology::Ring, Topology::Grid(2)] {
+            let size = PuzzleSize {
+                kinds: 2,
+                values: 4,
+                topology,
+            };
+            assert_eq!(solve_all(size, &rules), Err(UnfitRule(rules[0])));
+        }
+        let size = PuzzleSize {
+            kinds: 2,
+            values: 4,
+            topology: Topology::Line,
+        };
+        assert!(has_unique_solution(size, &rules).is_ok());
+    }
+}
@@ -10,3 +10,12 @@ where
         }
     }
 }
+
+/// Like `retry`, but gives up after `tries` generated values.
+pub fn retry_limited<T, G, C>(tries: usize, mut gen: G, check: C) -> Option<T>
+where
+    G: FnMut() -> T,
+    C: Fn(&T) -> bool,
+{
+    (0..tries).map(|_| gen()).find(check)
+}
@@ -1 +1,4 @@
 pub mod palette;
+#[cfg(feature = "pdf")]
+pub mod pdf;
+pub mod svg;
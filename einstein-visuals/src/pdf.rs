@@ -0,0 +1,18 @@
+use crate::svg::render_svg;
+use einstein_puzzle::rules::{PuzzleSize, Rule};
+use svg2pdf::usvg::{fontdb, PostProcessingSteps, Tree, TreeParsing, TreePostProc};
+
+pub use svg2pdf::usvg::Error;
+
+/// Renders the same page as `render_svg` into a standalone PDF document.
+/// Text is converted to outlines using the fonts installed in the system.
+pub fn render_pdf(size: PuzzleSize, rules: &[Rule], title: Option<&str>) -> Result<Vec<u8>, Error> {
+    let svg = render_svg(size, rules, title);
+    let mut tree = Tree::from_str(&svg, &Default::default())?;
+
+    let mut fonts = fontdb::Database::new();
+    fonts.load_system_fonts();
+    tree.postprocess(PostProcessingSteps::default(), &fonts);
+
+    Ok(svg2pdf::convert_tree(&tree, Default::default()))
+}
@@ -0,0 +1,466 @@
+use crate::palette::{default_palette, Color, ColorModifcation, Palette, PaletteColor};
+use einstein_puzzle::rules::{Clause, Junction, Kind, PuzzleSize, Rule, Thing, Topology};
+use palette::Srgb;
+use std::fmt::Write;
+
+// All lengths are in millimeters of an A4 page.
+const PAGE_WIDTH: f32 = 210.0;
+const PAGE_HEIGHT: f32 = 297.0;
+const MARGIN: f32 = 15.0;
+const TITLE_HEIGHT: f32 = 12.0;
+const MAX_GRID_TILE: f32 = 14.0;
+const RULE_TILE: f32 = 9.0;
+const GAP: f32 = 3.0;
+const SECTION_GAP: f32 = 8.0;
+const RING_MARK_HEIGHT: f32 = 3.0;
+// In tiles.
+const EITHER_LABEL_WIDTH: f32 = 1.5;
+
+fn hex(color: &Color) -> String {
+    let srgb: Srgb<u8> = Srgb::<f32>::from_linear(*color).into_format();
+    format!("#{:02x}{:02x}{:02x}", srgb.red, srgb.green, srgb.blue)
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+struct Canvas<'p> {
+    palette: &'p Palette,
+    out: String,
+}
+
+impl Canvas<'_> {
+    fn color(&self, color: PaletteColor) -> String {
+        hex(self.palette.get(color, ColorModifcation::Normal))
+    }
+
+    fn faded(&self, color: PaletteColor) -> String {
+        hex(self.palette.get(color, ColorModifcation::Faded))
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, fill: &str, stroke: &str) {
+        let _ = writeln!(
+            self.out,
+            r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" rx="{:.2}" fill="{}" stroke="{}" stroke-width="0.3"/>"#,
+            x,
+            y,
+            width,
+            height,
+            width.min(height) * 0.12,
+            fill,
+            stroke
+        );
+    }
+
+    fn text(&mut self, x: f32, y: f32, font_size: f32, color: &str, text: &str) {
+        let _ = writeln!(
+            self.out,
+            r#"<text x="{:.2}" y="{:.2}" font-family="DejaVu Sans, Liberation Sans, Arial, sans-serif" font-size="{:.2}" fill="{}" text-anchor="middle" dominant-baseline="central">{}</text>"#,
+            x,
+            y,
+            font_size,
+            color,
+            escape(text)
+        );
+    }
+
+    fn path(&mut self, data: &str, color: &str, width: f32) {
+        let _ = writeln!(
+            self.out,
+            r#"<path d="{}" fill="none" stroke="{}" stroke-width="{:.2}" stroke-linecap="round" stroke-linejoin="round"/>"#,
+            data, color, width
+        );
+    }
+
+    fn dot(&mut self, x: f32, y: f32, radius: f32, color: &str) {
+        let _ = writeln!(
+            self.out,
+            r#"<circle cx="{:.2}" cy="{:.2}" r="{:.2}" fill="{}"/>"#,
+            x, y, radius, color
+        );
+    }
+
+    fn double_arrow(&mut self, x1: f32, x2: f32, y: f32, head: f32, color: &str) {
+        let data = format!(
+            "M {x1:.2} {y:.2} L {x2:.2} {y:.2} \
+             M {:.2} {:.2} L {x1:.2} {y:.2} L {:.2} {:.2} \
+             M {:.2} {:.2} L {x2:.2} {y:.2} L {:.2} {:.2}",
+            x1 + head,
+            y - head,
+            x1 + head,
+            y + head,
+            x2 - head,
+            y - head,
+            x2 - head,
+            y + head,
+        );
+        self.path(&data, color, head * 0.5);
+    }
+
+    fn cross(&mut self, x: f32, y: f32, size: f32, color: &str) {
+        let data = format!(
+            "M {x:.2} {y:.2} L {:.2} {:.2} M {:.2} {y:.2} L {x:.2} {:.2}",
+            x + size,
+            y + size,
+            x + size,
+            y + size,
+        );
+        self.path(&data, color, size * 0.08);
+    }
+
+    fn thing(&mut self, x: f32, y: f32, tile: f32, thing: Thing) {
+        let fill = self.color(PaletteColor::for_kind(thing.row));
+        let stroke = self.color(PaletteColor::Stroke);
+        self.rect(x, y, tile, tile, &fill, &stroke);
+        self.text(
+            x + tile / 2.0,
+            y + tile / 2.0,
+            tile * 0.55,
+            &stroke,
+            &thing.to_string(),
+        );
+    }
+
+    fn empty_cell(&mut self, x: f32, y: f32, tile: f32, kind: Kind) {
+        let fill = self.faded(PaletteColor::for_kind(kind));
+        let stroke = self.color(PaletteColor::Border);
+        self.rect(x, y, tile, tile, &fill, &stroke);
+    }
+
+    fn rule(&mut self, x: f32, y: f32, tile: f32, rule: Rule) {
+        self.out.push_str("<g class=\"rule\">\n");
+        self.glyph(x, y, tile, rule);
+        self.out.push_str("</g>\n");
+    }
+
+    fn clause(&mut self, x: f32, y: f32, tile: f32, clause: Clause) {
+        match clause {
+            Clause::At(col, thing) => {
+                let color = self.color(PaletteColor::Stroke);
+                self.thing(x, y, tile, thing);
+                self.text(
+                    x + tile * 1.5,
+                    y + tile / 2.0,
+                    tile * 0.55,
+                    &color,
+                    &(col + 1).to_string(),
+                );
+            }
+            _ => self.glyph(x, y, tile, clause.rule()),
+        }
+    }
+
+    fn glyph(&mut self, x: f32, y: f32, tile: f32, rule: Rule) {
+        match rule {
+            Rule::Near(thing1, thing2) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Near);
+                self.double_arrow(
+                    x + tile * 1.15,
+                    x + tile * 1.85,
+                    y + tile / 2.0,
+                    tile * 0.12,
+                    &color,
+                );
+            }
+            Rule::Direction(thing1, thing2) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Direction);
+                for step in [1.25, 1.5, 1.75] {
+                    self.dot(x + tile * step, y + tile / 2.0, tile * 0.06, &color);
+                }
+            }
+            Rule::Under(thing1, thing2) => {
+                let color = self.color(PaletteColor::Under);
+                self.thing(x, y, tile, thing1);
+                self.thing(x, y + tile, tile, thing2);
+                self.rect(x, y, tile, tile * 2.0, "none", &color);
+            }
+            Rule::Between(thing1, thing2, thing3) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile, y, tile, thing2);
+                self.thing(x + tile * 2.0, y, tile, thing3);
+                let color = self.color(PaletteColor::Between);
+                self.double_arrow(
+                    x + tile * 0.5,
+                    x + tile * 2.5,
+                    y + tile * 0.12,
+                    tile * 0.1,
+                    &color,
+                );
+            }
+            Rule::NotNear(thing1, thing2) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Near);
+                self.double_arrow(
+                    x + tile * 1.15,
+                    x + tile * 1.85,
+                    y + tile / 2.0,
+                    tile * 0.12,
+                    &color,
+                );
+                self.cross(x + tile * 1.25, y + tile * 0.25, tile * 0.5, &color);
+            }
+            Rule::NotUnder(thing1, thing2) => {
+                let color = self.color(PaletteColor::Under);
+                self.thing(x, y, tile, thing1);
+                self.thing(x, y + tile, tile, thing2);
+                self.rect(x, y, tile, tile * 2.0, "none", &color);
+                self.cross(x + tile * 0.25, y + tile * 0.75, tile * 0.5, &color);
+            }
+            Rule::NotAt(col, thing) => {
+                let color = self.color(PaletteColor::Stroke);
+                self.thing(x, y, tile, thing);
+                self.cross(x + tile * 1.25, y + tile * 0.25, tile * 0.5, &color);
+                self.text(
+                    x + tile * 2.5,
+                    y + tile / 2.0,
+                    tile * 0.55,
+                    &color,
+                    &(col + 1).to_string(),
+                );
+            }
+            Rule::LeftOf(thing1, thing2) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Direction);
+                let (y, head) = (y + tile * 0.6, tile * 0.12);
+                let data = format!(
+                    "M {:.2} {y:.2} L {:.2} {y:.2} M {:.2} {:.2} L {:.2} {y:.2} L {:.2} {:.2}",
+                    x + tile * 1.15,
+                    x + tile * 1.85,
+                    x + tile * 1.85 - head,
+                    y - head,
+                    x + tile * 1.85,
+                    x + tile * 1.85 - head,
+                    y + head,
+                );
+                self.path(&data, &color, head * 0.5);
+                self.text(x + tile * 1.5, y - tile * 0.3, tile * 0.35, &color, "1");
+            }
+            Rule::Distance(thing1, thing2, distance) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Near);
+                self.double_arrow(
+                    x + tile * 1.15,
+                    x + tile * 1.85,
+                    y + tile * 0.6,
+                    tile * 0.12,
+                    &color,
+                );
+                self.text(
+                    x + tile * 1.5,
+                    y + tile * 0.3,
+                    tile * 0.35,
+                    &color,
+                    &distance.to_string(),
+                );
+            }
+            Rule::Either(clause1, clause2, junction) => {
+                let color = self.color(PaletteColor::Stroke);
+                let label = match junction {
+                    Junction::Inclusive => "or",
+                    Junction::Exclusive => "either",
+                };
+                let (width1, _) = clause_size(clause1, tile);
+                self.clause(x, y, tile, clause1);
+                self.text(
+                    x + width1 + EITHER_LABEL_WIDTH * tile / 2.0,
+                    y + tile / 2.0,
+                    tile * 0.45,
+                    &color,
+                    label,
+                );
+                self.clause(x + width1 + EITHER_LABEL_WIDTH * tile, y, tile, clause2);
+            }
+            Rule::Above(thing1, thing2) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Under);
+                let (x, head) = (x + tile * 1.5, tile * 0.12);
+                let data = format!(
+                    "M {x:.2} {:.2} L {x:.2} {:.2} M {:.2} {:.2} L {x:.2} {:.2} L {:.2} {:.2}",
+                    y + tile * 0.2,
+                    y + tile * 0.8,
+                    x - head,
+                    y + tile * 0.8 - head,
+                    y + tile * 0.8,
+                    x + head,
+                    y + tile * 0.8 - head,
+                );
+                self.path(&data, &color, head * 0.5);
+            }
+            Rule::SameRow(thing1, thing2) => {
+                self.thing(x, y, tile, thing1);
+                self.thing(x + tile * 2.0, y, tile, thing2);
+                let color = self.color(PaletteColor::Between);
+                let data = format!(
+                    "M {:.2} {:.2} L {:.2} {:.2} M {:.2} {:.2} L {:.2} {:.2}",
+                    x + tile * 1.15,
+                    y + tile * 0.4,
+                    x + tile * 1.85,
+                    y + tile * 0.4,
+                    x + tile * 1.15,
+                    y + tile * 0.6,
+                    x + tile * 1.85,
+                    y + tile * 0.6,
+                );
+                self.path(&data, &color, tile * 0.06);
+            }
+            Rule::Open(..) => {}
+        }
+    }
+}
+
+fn clause_size(clause: Clause, tile: f32) -> (f32, f32) {
+    match clause {
+        Clause::At(..) => (tile * 2.0, tile),
+        Clause::Under(..) => (tile, tile * 2.0),
+        Clause::Near(..) | Clause::LeftOf(..) => (tile * 3.0, tile),
+    }
+}
+
+fn rule_size(rule: Rule) -> (f32, f32) {
+    if let Rule::Either(clause1, clause2, _) = rule {
+        let (width1, height1) = clause_size(clause1, RULE_TILE);
+        let (width2, height2) = clause_size(clause2, RULE_TILE);
+        (
+            width1 + EITHER_LABEL_WIDTH * RULE_TILE + width2,
+            height1.max(height2),
+        )
+    } else if rule.is_vertical() {
+        (RULE_TILE, RULE_TILE * 2.0)
+    } else {
+        (RULE_TILE * 3.0, RULE_TILE)
+    }
+}
+
+// Places rules left to right, wrapping at the right margin. Returns the bottom edge.
+fn flow_rules(canvas: &mut Canvas, rules: &[Rule], top: f32) -> f32 {
+    let mut x = MARGIN;
+    let mut y = top;
+    let mut line_height: f32 = 0.0;
+    for rule in rules {
+        let (width, height) = rule_size(*rule);
+        if x > MARGIN && x + width > PAGE_WIDTH - MARGIN {
+            x = MARGIN;
+            y += line_height + GAP;
+            line_height = 0.0;
+        }
+        canvas.rule(x, y, RULE_TILE, *rule);
+        x += width + GAP;
+        line_height = line_height.max(height);
+    }
+    y + line_height
+}
+
+/// Renders a printable page with the empty grid, the `Open` givens and all clues.
+pub fn render_svg(size: PuzzleSize, rules: &[Rule], title: Option<&str>) -> String {
+    let mut canvas = Canvas {
+        palette: default_palette(),
+        out: String::new(),
+    };
+
+    let mut top = MARGIN;
+    if let Some(title) = title {
+        let color = canvas.color(PaletteColor::Stroke);
+        canvas.text(
+            PAGE_WIDTH / 2.0,
+            top + TITLE_HEIGHT / 2.0,
+            7.0,
+            &color,
+            title,
+        );
+        top += TITLE_HEIGHT;
+    }
+
+    // a grid puzzle is printed as a stack of rows of houses
+    let tile = ((PAGE_WIDTH - 2.0 * MARGIN) / f32::from(size.width())).min(MAX_GRID_TILE);
+    let left = (PAGE_WIDTH - tile * f32::from(size.width())) / 2.0;
+    let row_height = tile * f32::from(size.kinds) + GAP;
+    for kind in size.iter_kinds() {
+        for col in 0..size.values {
+            let x = left + tile * f32::from(col % size.width());
+            let y = top + row_height * f32::from(col / size.width()) + tile * f32::from(kind.0);
+            let given = rules.iter().find_map(|rule| match *rule {
+                Rule::Open(c, thing) if c == col && thing.row == kind => Some(thing),
+                _ => None,
+            });
+            match given {
+                Some(thing) => canvas.thing(x, y, tile, thing),
+                None => canvas.empty_cell(x, y, tile, kind),
+            }
+        }
+    }
+    let mut bottom = top + row_height * f32::from(size.grid_rows()) - GAP;
+    if size.topology == Topology::Ring {
+        // join the first and the last columns of a round table
+        let color = canvas.color(PaletteColor::Stroke);
+        let (x1, x2) = (
+            left + tile / 2.0,
+            left + tile * (f32::from(size.values) - 0.5),
+        );
+        let y = bottom + RING_MARK_HEIGHT;
+        canvas.path(
+            &format!(
+                "M {x1:.2} {bottom:.2} L {x1:.2} {y:.2} L {x2:.2} {y:.2} L {x2:.2} {bottom:.2}"
+            ),
+            &color,
+            0.6,
+        );
+        bottom = y;
+    }
+
+    let (vertical, horizontal): (Vec<Rule>, Vec<Rule>) = rules
+        .iter()
+        .filter(|rule| rule.is_non_trivial())
+        .partition(|rule| rule.is_vertical());
+    for group in [horizontal, vertical] {
+        if !group.is_empty() {
+            bottom = flow_rules(&mut canvas, &group, bottom + SECTION_GAP);
+        }
+    }
+
+    let height = PAGE_HEIGHT.max(bottom + MARGIN);
+    format!(
+        concat!(
+            r#"<?xml version="1.0" encoding="UTF-8"?>"#,
+            "\n",
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}mm" height="{h:.2}mm" viewBox="0 0 {w} {h:.2}">"#,
+            "\n",
+            r#"<rect width="100%" height="100%" fill="{bg}"/>"#,
+            "\n{body}</svg>\n"
+        ),
+        w = PAGE_WIDTH,
+        h = height,
+        bg = hex(&Color::new(1.0, 1.0, 1.0)),
+        body = canvas.out
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use einstein_puzzle::difficulty::Difficulty;
+    use einstein_puzzle::puzzle_gen::generate_puzzle;
+
+    #[test]
+    fn test_render_svg() {
+        let size = PuzzleSize::default();
+        let (_puzzle, rules) = generate_puzzle(size, Difficulty::Medium, 42).unwrap();
+        let svg = render_svg(size, &rules, Some("Puzzle <1>"));
+
+        assert!(svg.starts_with("<?xml"));
+        assert!(svg.ends_with("</svg>\n"));
+        assert!(svg.contains("Puzzle &lt;1&gt;"));
+        let clues = rules.iter().filter(|rule| rule.is_non_trivial()).count();
+        assert_eq!(svg.matches("<g class=\"rule\">").count(), clues);
+    }
+}
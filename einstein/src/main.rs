@@ -5,6 +5,7 @@ mod util;
 #[macro_use]
 mod resources;
 mod audio;
+mod pool;
 mod storage;
 mod ui;
 
@@ -18,12 +19,14 @@ use dirs::home_dir;
 use std::fs::create_dir_all;
 use std::process::exit;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 fn real_main() -> Result<()> {
     let home = home_dir().ok_or_else(|| format_err!("Home directory is not detected."))?;
     create_dir_all(home.join(".einstein"))?;
 
     let state = Rc::new(RefCell::new(storage::Storage::load().unwrap_or_default()));
+    let pool = Arc::new(Mutex::new(pool::Pool::load().unwrap_or_default()));
 
     let sdl_context = sdl2::init().map_err(|e| format_err!("{}", e))?;
     let video_subsystem = sdl_context.video().map_err(|e| format_err!("{}", e))?;
@@ -55,7 +58,7 @@ fn real_main() -> Result<()> {
             audio: &audio,
         };
 
-        let mut menu = make_menu(get_messages(), state.clone())?;
+        let mut menu = make_menu(get_messages(), state.clone(), pool)?;
         context.main_loop(&mut menu)?;
     }
 
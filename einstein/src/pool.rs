@@ -0,0 +1,239 @@
+use crate::cell::RefCell;
+use crate::error::*;
+use crate::resources::manager::ResourceManager;
+use crate::storage::{app_dir, read_file, write_file, Storage};
+use crate::ui::brick::Brick;
+use crate::ui::common::Size;
+use crate::ui::component::game::generate_fittable_puzzle;
+use crate::ui::context::Context;
+use crate::ui::widget::widget::*;
+use einstein_puzzle::code::PuzzleCode;
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::puzzle_gen::{GenerationControl, GENERATOR_VERSION};
+use einstein_puzzle::rules::{PuzzleSize, Rule, SolvedPuzzle};
+use never::Never;
+use rand::thread_rng;
+use serde::{Deserialize, Serialize};
+use std::fs::{create_dir_all, rename};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+pub const PUZZLES_PER_KIND: usize = 3;
+
+fn pool_path() -> Result<PathBuf> {
+    Ok(app_dir()?.join("pool.json"))
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PooledPuzzle {
+    pub code: PuzzleCode,
+    pub solved_puzzle: SolvedPuzzle,
+    pub rules: Vec<Rule>,
+}
+
+impl PooledPuzzle {
+    fn is_of(&self, size: PuzzleSize, difficulty: Difficulty) -> bool {
+        self.code.size == size && self.code.difficulty == difficulty
+    }
+}
+
+/// Puzzles generated ahead of time, so a new game starts at once.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Pool {
+    pub puzzles: Vec<PooledPuzzle>,
+}
+
+impl Pool {
+    pub fn load_from_file(filename: &Path) -> Result<Self> {
+        let buf = read_file(filename)?;
+        let mut pool: Pool = serde_json::from_slice(&buf)?;
+        // puzzles of an older generator would not match their codes
+        pool.puzzles
+            .retain(|puzzle| puzzle.code.version == GENERATOR_VERSION);
+        Ok(pool)
+    }
+
+    pub fn load() -> Result<Self> {
+        Self::load_from_file(&pool_path()?)
+    }
+
+    pub fn save_to_file(&self, filename: &Path) -> Result<()> {
+        let dump = serde_json::to_vec(self)?;
+        // the game may quit while a refill is saved
+        let temp = filename.with_extension("json.tmp");
+        write_file(&temp, &dump)?;
+        rename(&temp, filename)?;
+        Ok(())
+    }
+
+    pub fn save(&self) -> Result<()> {
+        create_dir_all(app_dir()?)?;
+        self.save_to_file(&pool_path()?)
+    }
+
+    pub fn count(&self, size: PuzzleSize, difficulty: Difficulty) -> usize {
+        self.puzzles
+            .iter()
+            .filter(|puzzle| puzzle.is_of(size, difficulty))
+            .count()
+    }
+
+    pub fn take(&mut self, size: PuzzleSize, difficulty: Difficulty) -> Option<PooledPuzzle> {
+        let index = self
+            .puzzles
+            .iter()
+            .position(|puzzle| puzzle.is_of(size, difficulty))?;
+        Some(self.puzzles.remove(index))
+    }
+}
+
+/// Takes a puzzle out of the pool and saves the rest, so it is never played twice.
+pub fn take_pooled(
+    pool: &Mutex<Pool>,
+    size: PuzzleSize,
+    difficulty: Difficulty,
+) -> Option<PooledPuzzle> {
+    let mut pool = pool.lock().unwrap();
+    let puzzle = pool.take(size, difficulty)?;
+    let _ = pool.save();
+    Some(puzzle)
+}
+
+/// Invisible widget refilling the pool for the chosen size and difficulty
+/// on a worker thread, one puzzle at a time, while it gets ticks.
+pub struct PoolRefill {
+    pool: Arc<Mutex<Pool>>,
+    storage: Rc<RefCell<Storage>>,
+    worker: Option<((PuzzleSize, Difficulty), JoinHandle<bool>)>,
+    cancel: Arc<AtomicBool>,
+    failed: Vec<(PuzzleSize, Difficulty)>,
+}
+
+impl PoolRefill {
+    pub fn new(pool: Arc<Mutex<Pool>>, storage: Rc<RefCell<Storage>>) -> Self {
+        Self {
+            pool,
+            storage,
+            worker: None,
+            cancel: Arc::new(AtomicBool::new(false)),
+            failed: Vec::new(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let size = self.storage.borrow().size;
+        let difficulty = self.storage.borrow().difficulty;
+        if self.failed.contains(&(size, difficulty))
+            || self.pool.lock().unwrap().count(size, difficulty) >= PUZZLES_PER_KIND
+        {
+            return;
+        }
+
+        let pool = self.pool.clone();
+        let cancel = self.cancel.clone();
+        let worker = thread::spawn(move || {
+            let Ok(code) = PuzzleCode::random(size, difficulty, &mut thread_rng()) else {
+                return false;
+            };
+            let mut control = GenerationControl::new().with_cancel(&cancel);
+            let Ok((solved_puzzle, rules)) = generate_fittable_puzzle(&code, &mut control) else {
+                return false;
+            };
+            let mut pool = pool.lock().unwrap();
+            pool.puzzles.push(PooledPuzzle {
+                code,
+                solved_puzzle,
+                rules,
+            });
+            let _ = pool.save();
+            true
+        });
+        self.worker = Some(((size, difficulty), worker));
+    }
+}
+
+impl Drop for PoolRefill {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Widget<Never> for PoolRefill {
+    fn get_size(&self) -> Size {
+        Size::EMPTY
+    }
+
+    fn on_event(&mut self, event: &Event, _context: &dyn Context) -> EventResult<Never> {
+        if let Event::Tick = *event {
+            match self.worker.take() {
+                Some((kind, worker)) if !worker.is_finished() => self.worker = Some((kind, worker)),
+                Some((kind, worker)) => {
+                    // do not retry a kind of puzzle that cannot be generated
+                    if !worker.join().unwrap_or(false) {
+                        self.failed.push(kind);
+                    }
+                }
+                None => self.refill(),
+            }
+        }
+        Ok(EventReaction::empty())
+    }
+
+    fn draw(&self, _resource_manager: &dyn ResourceManager) -> Brick {
+        Brick::new(0, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use einstein_puzzle::rules::Topology;
+
+    #[test]
+    fn test_take_by_size_and_difficulty() {
+        let size = PuzzleSize {
+            kinds: 4,
+            values: 4,
+            topology: Topology::Line,
+        };
+        let mut pool = Pool::default();
+        for (seed, difficulty) in [(1, Difficulty::Easy), (2, Difficulty::Medium)] {
+            let code = PuzzleCode::new(size, difficulty, seed).unwrap();
+            let (solved_puzzle, rules) = code.generate().unwrap();
+            pool.puzzles.push(PooledPuzzle {
+                code,
+                solved_puzzle,
+                rules,
+            });
+        }
+        assert_eq!(pool.count(size, Difficulty::Medium), 1);
+        assert_eq!(pool.count(PuzzleSize::default(), Difficulty::Medium), 0);
+        let puzzle = pool.take(size, Difficulty::Medium).unwrap();
+        assert_eq!(puzzle.code.seed, 2);
+        assert!(pool.take(size, Difficulty::Medium).is_none());
+        assert_eq!(pool.count(size, Difficulty::Easy), 1);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let code = PuzzleCode::new(PuzzleSize::default(), Difficulty::Medium, 7).unwrap();
+        let (solved_puzzle, rules) = code.generate().unwrap();
+        let pool = Pool {
+            puzzles: vec![PooledPuzzle {
+                code,
+                solved_puzzle: solved_puzzle.clone(),
+                rules: rules.clone(),
+            }],
+        };
+        let path = std::env::temp_dir().join(format!("einstein-pool-{}.json", std::process::id()));
+        pool.save_to_file(&path).unwrap();
+        let loaded = Pool::load_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(loaded.puzzles.len(), 1);
+        assert_eq!(loaded.puzzles[0].solved_puzzle, solved_puzzle);
+        assert_eq!(loaded.puzzles[0].rules, rules);
+    }
+}
@@ -1,3 +1,6 @@
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::rules::Propagation;
+
 pub struct Messages<'a> {
     pub rules: &'a str,
     pub close: &'a str,
@@ -31,8 +34,54 @@ pub struct Messages<'a> {
     pub switch: &'a str,
     pub save: &'a str,
     pub help: &'a str,
+    pub hint: &'a str,
     pub loading: &'a str,
+    pub generation_failed: &'a str,
     pub einstein_flowix: &'a str,
     pub enable_sound: &'a str,
     pub volume: &'a str,
+    pub board_size: &'a str,
+    pub difficulty: &'a str,
+    pub easy: &'a str,
+    pub medium: &'a str,
+    pub hard: &'a str,
+    pub expert: &'a str,
+    pub enter_code: &'a str,
+    pub puzzle_code: &'a str,
+    pub invalid_puzzle_code: &'a str,
+    pub daily_puzzle: &'a str,
+    pub play: &'a str,
+    pub today: &'a str,
+    pub not_solved: &'a str,
+    pub current_streak: &'a str,
+    pub best_streak: &'a str,
+    pub weekdays: [&'a str; 7],
+    pub months: [&'a str; 12],
+    pub clause_or: &'a str,
+    pub clause_either: &'a str,
+    pub round_table: &'a str,
+    pub grid: &'a str,
+    pub auto_deduction: &'a str,
+    pub singles: &'a str,
+    pub subsets: &'a str,
+    pub arc_consistency: &'a str,
+}
+
+impl Messages<'_> {
+    pub fn difficulty_name(&self, difficulty: Difficulty) -> &str {
+        match difficulty {
+            Difficulty::Easy => self.easy,
+            Difficulty::Medium => self.medium,
+            Difficulty::Hard => self.hard,
+            Difficulty::Expert => self.expert,
+        }
+    }
+
+    pub fn propagation_name(&self, propagation: Propagation) -> &str {
+        match propagation {
+            Propagation::Singles => self.singles,
+            Propagation::Subsets => self.subsets,
+            Propagation::ArcConsistency => self.arc_consistency,
+        }
+    }
 }
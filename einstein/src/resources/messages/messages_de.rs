@@ -33,8 +33,48 @@ pub const MESSAGES: Messages<'static> = Messages {
     switch: "Umschalten",
     save: "Speichern",
     help: "Hilfe",
+    hint: "Tipp",
     loading: "Lade....  Bitte warten....",
+    generation_failed: "Rätsel konnte nicht erzeugt werden",
     einstein_flowix: "Einstein-Puzzle von Flowix Games",
     enable_sound: "Audio aktivieren",
     volume: "Lautstärke:",
+    board_size: "Spielfeld:",
+    difficulty: "Schwierigkeit:",
+    easy: "Leicht",
+    medium: "Mittel",
+    hard: "Schwer",
+    expert: "Experte",
+    enter_code: "Rätselcode eingeben",
+    puzzle_code: "Rätselcode:",
+    invalid_puzzle_code: "Dieser Rätselcode ist ungültig",
+    daily_puzzle: "Tägliches Rätsel",
+    play: "Spielen",
+    today: "Heute:",
+    not_solved: "noch nicht gelöst",
+    current_streak: "Aktuelle Serie:",
+    best_streak: "Beste Serie:",
+    weekdays: ["Mo", "Di", "Mi", "Do", "Fr", "Sa", "So"],
+    months: [
+        "Januar",
+        "Februar",
+        "März",
+        "April",
+        "Mai",
+        "Juni",
+        "Juli",
+        "August",
+        "September",
+        "Oktober",
+        "November",
+        "Dezember",
+    ],
+    clause_or: "oder",
+    clause_either: "entweder",
+    round_table: "runder Tisch",
+    grid: "Raster",
+    auto_deduction: "Auto-Deduktion:",
+    singles: "Einzelne",
+    subsets: "Teilmengen",
+    arc_consistency: "Alle Hinweise",
 };
@@ -33,8 +33,48 @@ pub const MESSAGES: Messages<'static> = Messages {
     switch: "Switch",
     save: "Save",
     help: "Help",
+    hint: "Hint",
     loading: "Loading....  Please wait....",
+    generation_failed: "Cannot generate a puzzle",
     einstein_flowix: "Einstein puzzle by Flowix Games",
     enable_sound: "Enable sound",
     volume: "Volume:",
+    board_size: "Board size:",
+    difficulty: "Difficulty:",
+    easy: "Easy",
+    medium: "Medium",
+    hard: "Hard",
+    expert: "Expert",
+    enter_code: "Enter Puzzle Code",
+    puzzle_code: "Puzzle code:",
+    invalid_puzzle_code: "This puzzle code is not valid",
+    daily_puzzle: "Daily Puzzle",
+    play: "Play",
+    today: "Today:",
+    not_solved: "not solved yet",
+    current_streak: "Current streak:",
+    best_streak: "Best streak:",
+    weekdays: ["Mo", "Tu", "We", "Th", "Fr", "Sa", "Su"],
+    months: [
+        "January",
+        "February",
+        "March",
+        "April",
+        "May",
+        "June",
+        "July",
+        "August",
+        "September",
+        "October",
+        "November",
+        "December",
+    ],
+    clause_or: "or",
+    clause_either: "either",
+    round_table: "round table",
+    grid: "grid",
+    auto_deduction: "Auto-deduction:",
+    singles: "Singles",
+    subsets: "Subsets",
+    arc_consistency: "All clues",
 };
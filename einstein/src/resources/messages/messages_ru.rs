@@ -33,8 +33,48 @@ pub const MESSAGES: Messages<'static> = Messages {
     switch: "Скрытые",
     save: "Сохранить",
     help: "Справка",
+    hint: "Подсказка",
     loading: "Загружается....  Пожалуйста ждите....",
+    generation_failed: "Не удалось создать головоломку",
     einstein_flowix: "Головоломка Эйнштейна от Flowix Games",
     enable_sound: "Включить звуки",
     volume: "Громкость:",
+    board_size: "Размер поля:",
+    difficulty: "Сложность:",
+    easy: "Легко",
+    medium: "Средне",
+    hard: "Сложно",
+    expert: "Эксперт",
+    enter_code: "Ввести код",
+    puzzle_code: "Код головоломки:",
+    invalid_puzzle_code: "Неверный код головоломки",
+    daily_puzzle: "Головоломка дня",
+    play: "Играть",
+    today: "Сегодня:",
+    not_solved: "ещё не решена",
+    current_streak: "Текущая серия:",
+    best_streak: "Лучшая серия:",
+    weekdays: ["Пн", "Вт", "Ср", "Чт", "Пт", "Сб", "Вс"],
+    months: [
+        "Январь",
+        "Февраль",
+        "Март",
+        "Апрель",
+        "Май",
+        "Июнь",
+        "Июль",
+        "Август",
+        "Сентябрь",
+        "Октябрь",
+        "Ноябрь",
+        "Декабрь",
+    ],
+    clause_or: "или",
+    clause_either: "либо",
+    round_table: "круглый стол",
+    grid: "сетка",
+    auto_deduction: "Автовывод:",
+    singles: "Одиночки",
+    subsets: "Группы",
+    arc_consistency: "Все подсказки",
 };
@@ -58,10 +58,46 @@ pub const RULES: &[TextItem] = &[
         Karte ist immer zwischen den anderen zwei, aber es ist unbekannt, welche
         Karte sich auf der rechten Seite und welche sich auf der linken befindet.",
     ),
+    TextItem::Text(
+        "Eine Zahl über dem Pfeil eines Tipps gibt den genauen Abstand in
+        Spalten an. Ein Nachbar-Tipp mit einer Zahl bedeutet, dass zwei Karten
+        genau so viele Spalten voneinander entfernt sind, und ein Links-Rechts-Tipp
+        mit der Zahl 1 bedeutet, dass sich die erste Karte unmittelbar links von
+        der zweiten befindet.",
+    ),
+    TextItem::Text(
+        "Ein mit einem roten Kreuz durchgestrichener Tipp besagt das Gegenteil.
+        Ein durchgestrichener Nachbar-Tipp bedeutet, dass sich zwei Karten nicht
+        in benachbarten Spalten befinden, ein durchgestrichener vertikaler Tipp
+        bedeutet, dass sich zwei Karten nicht in derselben Spalte befinden, und
+        eine Karte, gefolgt von einem Kreuz und einer Zahl, befindet sich nicht
+        in der Spalte mit dieser Nummer.",
+    ),
     TextItem::Text(
         "Falls Sie einen Tipp nicht mehr benötigen, entfernen Sie ihn durch einen
         rechten Mausklick. Sie können entfernte Tipps immer durch Drücken des
         »Umschalte«-Knopfs wieder sehen.",
     ),
+    TextItem::Text(
+        "Ein Tipp mit zwei kleinen Tipps unter dem Wort \"oder\" bedeutet, dass
+        mindestens einer davon stimmt. Unter dem Wort \"entweder\" stimmt genau
+        einer der beiden kleinen Tipps und der andere ist falsch.",
+    ),
+    TextItem::Text(
+        "Bei einem Spiel am runden Tisch sitzen die Karten um einen Tisch, daher
+        ist die letzte Spalte neben der ersten. Eine Klammer unter dem Spielfeld
+        verbindet diese beiden Spalten. Nachbar-, Zwischen- und Abstands-Tipps
+        zählen die Spalten um den Tisch herum, und es gibt keine
+        Links-Rechts-Tipps.",
+    ),
+    TextItem::Text(
+        "Bei einem Raster-Spiel stehen die Häuser in mehreren Reihen, und jede
+        Reihe von Häusern wird als eigenes Band gezeigt. Ein Tipp mit einem
+        Pfeil nach unten bedeutet, dass die erste Karte im Haus direkt über dem
+        Haus der zweiten Karte liegt, und ein Tipp mit einem Doppelstrich
+        bedeutet, dass beide Karten in derselben Reihe von Häusern liegen.
+        Benachbarte Häuser können auch übereinander liegen, und Abstände
+        zählen die Schritte entlang der Reihen und Spalten.",
+    ),
     TextItem::Text("Wer ein Spiel lädt oder neu startet, kommt nicht in die Ruhmeshalle."),
 ];
@@ -52,6 +52,39 @@ pub const RULES: &[TextItem] = &[
         card is always between other two, but it is unknown, which card is located
         at the right side and which at the left.",
     ),
+    TextItem::Text(
+        "A number written above the arrow of a tip gives the exact distance
+        in columns.  A neighbour tip with a number means that two cards are
+        exactly that many columns apart, and a left-to-right tip with the
+        number 1 means that the first card is immediately to the left of the
+        second one.",
+    ),
+    TextItem::Text(
+        "A tip crossed out with a red cross states the opposite.  A crossed
+        out neighbour tip means that two cards are not in neighbour columns,
+        a crossed out vertical tip means that two cards are not in the same
+        column, and a card followed by a cross and a number is not located
+        in the column with that number.",
+    ),
+    TextItem::Text(
+        "A tip with two small tips under the word \"or\" means that at least
+        one of them is true.  Under the word \"either\" exactly one of the two
+        small tips is true and the other one is false.",
+    ),
+    TextItem::Text(
+        "In a round table game the cards sit around a table, so the last
+        column is next to the first one.  A bracket under the puzzle field
+        joins these two columns.  Neighbour, between and distance tips count
+        columns around the table, and there are no left-to-right tips.",
+    ),
+    TextItem::Text(
+        "In a grid game the houses stand in several rows, and every row of
+        houses is shown as a band of its own.  A tip with a down arrow means
+        that the first card is in the house right above the house of the
+        second card, and a tip with a double bar means that both cards are in
+        the same row of houses.  Neighbour houses may also be above or below
+        each other, and distances count the steps along rows and columns.",
+    ),
     TextItem::Text(
         "If you no longer need some tip, remove it by right mouse button click.
         You can always see removed tips by pressing 'Switch' button.",
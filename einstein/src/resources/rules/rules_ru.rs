@@ -63,6 +63,38 @@ pub const RULES: &[TextItem] = &[
         указанная в центре всегда находится между двумя другими, но какая 
         фишка правее центральной а какая левее - неизвестно.",
     ),
+    TextItem::Text(
+        "Число над стрелкой подсказки указывает точное расстояние в колонках.
+        Подсказка о соседстве с числом означает, что фишки находятся ровно
+        на таком расстоянии друг от друга, а подсказка «левее» с числом 1 -
+        что первая фишка находится непосредственно слева от второй.",
+    ),
+    TextItem::Text(
+        "Подсказка, перечёркнутая красным крестом, утверждает обратное.
+        Перечёркнутая подсказка о соседстве означает, что фишки не находятся
+        в соседних колонках, перечёркнутая вертикальная подсказка - что фишки
+        находятся в разных колонках, а фишка, за которой следуют крест и число,
+        не находится в колонке с этим номером.",
+    ),
+    TextItem::Text(
+        "Подсказка из двух маленьких подсказок под словом «или» означает, что
+        верна хотя бы одна из них.  Под словом «либо» верна ровно одна из двух
+        маленьких подсказок, а другая неверна.",
+    ),
+    TextItem::Text(
+        "В игре за круглым столом фишки сидят вокруг стола, поэтому последняя
+        колонка находится рядом с первой.  Скобка под игровым полем соединяет
+        эти две колонки.  Подсказки о соседстве, о нахождении между и о
+        расстоянии считают колонки по кругу, а подсказок «левее» нет.",
+    ),
+    TextItem::Text(
+        "В игре на сетке дома стоят в несколько рядов, и каждый ряд домов
+        показан отдельной полосой.  Подсказка со стрелкой вниз означает, что
+        первая фишка находится в доме прямо над домом второй фишки, а
+        подсказка с двойной чертой - что обе фишки находятся в одном ряду
+        домов.  Соседние дома могут находиться и друг над другом, а
+        расстояние считается шагами по рядам и колонкам.",
+    ),
     TextItem::Text(
         "Использованные подсказки удобно удалять пользуясь правой кнопкой мыши. 
         Удаленные подсказки можно посмотреть нажав на кнопку 'Скрытые'. 
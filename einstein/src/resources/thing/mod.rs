@@ -1,6 +1,9 @@
 use crate::resources::manager::Resource;
+use crate::ui::brick::*;
 use crate::ui::common::Rect;
+use crate::ui::widget::common::*;
 use einstein_puzzle::rules::Thing;
+use sdl2::pixels::Color;
 
 pub const LARGE_THINGS_ATLAS: Resource = resource!("./large.bmp");
 pub const LARGE_THINGS_ATLAS_HIGHLIGHTED: Resource = resource!("./large-h.bmp");
@@ -16,6 +19,22 @@ const LARGE_TILE_HEIGHT: i32 = 48;
 const SMALL_TILE_WIDTH: i32 = 16;
 const SMALL_TILE_HEIGHT: i32 = 16;
 
+// Both atlases hold 6 kinds of 6 values each.
+const ATLAS_KINDS: u8 = 6;
+const ATLAS_VALUES: u8 = 6;
+
+// Things beyond the atlas are drawn as numbered tiles, colored by kind.
+const KIND_COLORS: [(u8, u8, u8); 8] = [
+    (160, 32, 32),
+    (32, 128, 32),
+    (32, 64, 176),
+    (176, 128, 0),
+    (128, 32, 144),
+    (0, 128, 144),
+    (176, 80, 0),
+    (96, 96, 96),
+];
+
 pub fn get_thing_rect(thing: Thing) -> Rect {
     Rect::new(
         thing.value.0 as i32 * LARGE_TILE_WIDTH,
@@ -33,3 +52,60 @@ pub fn get_small_thing_rect(thing: Thing) -> Rect {
         SMALL_TILE_HEIGHT as u32,
     )
 }
+
+fn in_atlas(thing: Thing) -> bool {
+    thing.row.0 < ATLAS_KINDS && thing.value.0 < ATLAS_VALUES
+}
+
+fn draw_numbered_thing(thing: Thing, width: u32, height: u32, highlighted: bool) -> Brick {
+    let (r, g, b) = KIND_COLORS[thing.row.0 as usize % KIND_COLORS.len()];
+    let color = if highlighted {
+        Color::RGB(
+            r.saturating_add(64),
+            g.saturating_add(64),
+            b.saturating_add(64),
+        )
+    } else {
+        Color::RGB(r, g, b)
+    };
+    Brick::new(width, height)
+        .background(Background::Color(color))
+        .border(Border::Beveled(
+            Color::RGB(255, 255, 255),
+            Color::RGB(0, 0, 0),
+        ))
+        .text(
+            Text::new(thing.value.0 + 1)
+                .font_size(FontSize((height * 2 / 3).max(8) as u16))
+                .color(Color::RGB(255, 255, 255))
+                .shadow(),
+        )
+}
+
+pub fn draw_large_thing(thing: Thing, width: u32, height: u32, highlighted: bool) -> Brick {
+    if !in_atlas(thing) {
+        return draw_numbered_thing(thing, width, height, highlighted);
+    }
+    Brick::new(width, height).background(Background::Stretched(
+        if highlighted {
+            &LARGE_THINGS_ATLAS_HIGHLIGHTED
+        } else {
+            &LARGE_THINGS_ATLAS
+        },
+        Some(get_thing_rect(thing)),
+    ))
+}
+
+pub fn draw_small_thing(thing: Thing, width: u32, height: u32, highlighted: bool) -> Brick {
+    if !in_atlas(thing) {
+        return draw_numbered_thing(thing, width, height, highlighted);
+    }
+    Brick::new(width, height).background(Background::Stretched(
+        if highlighted {
+            &SMALL_THINGS_ATLAS_HIGHLIGHTED
+        } else {
+            &SMALL_THINGS_ATLAS
+        },
+        Some(get_small_thing_rect(thing)),
+    ))
+}
@@ -1,25 +1,29 @@
 use crate::error::*;
 use crate::ui::component::game::GamePrivate;
+use crate::util::date::Date;
 use dirs::home_dir;
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::rules::{Propagation, PuzzleSize};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fs::{create_dir_all, File};
 use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
-fn read_file(filename: &Path) -> Result<Vec<u8>> {
+pub fn read_file(filename: &Path) -> Result<Vec<u8>> {
     let mut file = File::open(filename)?;
     let mut buf = Vec::new();
     file.read_to_end(&mut buf)?;
     Ok(buf)
 }
 
-fn write_file(filename: &Path, buf: &[u8]) -> Result<()> {
+pub fn write_file(filename: &Path, buf: &[u8]) -> Result<()> {
     let mut file = File::create(filename)?;
     file.write_all(buf)?;
     Ok(())
 }
 
-fn app_dir() -> Result<PathBuf> {
+pub fn app_dir() -> Result<PathBuf> {
     let home = home_dir().ok_or_else(|| format_err!("Home directory is not detected."))?;
     let dir = home.join(".einstein");
     Ok(dir)
@@ -36,6 +40,26 @@ pub const MAX_SCORES: usize = 10;
 pub struct Score {
     pub name: String,
     pub score: u32,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub size: PuzzleSize,
+}
+
+/// Scores are ranked separately for every board size and difficulty.
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub struct ScoreTable {
+    pub size: PuzzleSize,
+    pub difficulty: Difficulty,
+}
+
+impl Score {
+    pub fn table(&self) -> ScoreTable {
+        ScoreTable {
+            size: self.size,
+            difficulty: self.difficulty,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Default, Clone, Debug)]
@@ -44,32 +68,92 @@ pub struct Scores(pub Vec<Score>);
 impl Scores {
     pub fn init(&mut self) {
         self.0.sort_by_key(|score| score.score);
-        self.0.truncate(MAX_SCORES);
+        let mut tables: Vec<ScoreTable> = Vec::new();
+        for score in &self.0 {
+            if !tables.contains(&score.table()) {
+                tables.push(score.table());
+            }
+        }
+        for table in tables {
+            self.truncate(table);
+        }
+    }
+
+    fn truncate(&mut self, table: ScoreTable) {
+        let mut count = 0;
+        self.0.retain(|score| {
+            if score.table() != table {
+                return true;
+            }
+            count += 1;
+            count <= MAX_SCORES
+        });
     }
 
-    pub fn is_deserving(&self, challenger: u32) -> bool {
-        self.0.len() < MAX_SCORES
+    pub fn table(&self, table: ScoreTable) -> impl Iterator<Item = &Score> {
+        self.0.iter().filter(move |score| score.table() == table)
+    }
+
+    pub fn is_deserving(&self, table: ScoreTable, challenger: u32) -> bool {
+        self.table(table).count() < MAX_SCORES
             || self
-                .0
+                .table(table)
                 .last()
                 .map(|last| challenger < last.score)
                 .unwrap_or(false)
     }
 
     pub fn add_score_entry(&mut self, entry: Score) -> Option<usize> {
-        if self.0.is_empty() {
-            self.0.push(entry);
-            return Some(0);
-        }
-        let index = self.0.iter().position(|ref e| e.score > entry.score);
-        match index {
-            Some(index) if index < MAX_SCORES => {
-                self.0.insert(index, entry);
-                self.0.truncate(MAX_SCORES);
-                Some(index)
-            }
-            _ => None,
+        let table = entry.table();
+        let index = self
+            .table(table)
+            .position(|e| e.score > entry.score)
+            .unwrap_or_else(|| self.table(table).count());
+        if index >= MAX_SCORES {
+            return None;
         }
+        let position = self
+            .0
+            .iter()
+            .position(|e| e.score > entry.score)
+            .unwrap_or(self.0.len());
+        self.0.insert(position, entry);
+        self.truncate(table);
+        Some(index)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct DailyRecord {
+    pub time: u32,
+    pub streak: u32,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct DailyRecords(pub BTreeMap<Date, DailyRecord>);
+
+impl DailyRecords {
+    pub fn get(&self, date: Date) -> Option<&DailyRecord> {
+        self.0.get(&date)
+    }
+
+    pub fn record(&mut self, date: Date, time: u32) {
+        let streak = self.get(date.add_days(-1)).map_or(0, |r| r.streak) + 1;
+        self.0
+            .entry(date)
+            .and_modify(|record| record.time = record.time.min(time))
+            .or_insert(DailyRecord { time, streak });
+    }
+
+    /// Streak still alive on `today`: it may end today or, if today's puzzle is not solved yet, yesterday.
+    pub fn current_streak(&self, today: Date) -> u32 {
+        self.get(today)
+            .or_else(|| self.get(today.add_days(-1)))
+            .map_or(0, |r| r.streak)
+    }
+
+    pub fn best_streak(&self) -> u32 {
+        self.0.values().map(|r| r.streak).max().unwrap_or(0)
     }
 }
 
@@ -86,6 +170,14 @@ pub struct Storage {
     pub last_name: Option<String>,
     pub scores: Scores,
     pub saved_games: [Option<SavedGame>; MAX_SLOTS],
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub size: PuzzleSize,
+    #[serde(default)]
+    pub daily: DailyRecords,
+    #[serde(default)]
+    pub propagation: Propagation,
 }
 
 impl Storage {
@@ -111,3 +203,24 @@ impl Storage {
         self.save_to_file(&storage_path()?)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_streak() {
+        let mut daily = DailyRecords::default();
+        let today = Date::new(2024, 3, 1);
+        daily.record(today.add_days(-3), 100);
+        daily.record(today.add_days(-2), 200);
+        daily.record(today.add_days(-1), 300);
+        assert_eq!(daily.current_streak(today), 3);
+        daily.record(today, 50);
+        daily.record(today, 70);
+        assert_eq!(daily.get(today).unwrap().time, 50);
+        assert_eq!(daily.current_streak(today), 4);
+        assert_eq!(daily.current_streak(today.add_days(2)), 0);
+        assert_eq!(daily.best_streak(), 4);
+    }
+}
@@ -136,6 +136,12 @@ impl Brick {
                 });
                 sprite(canvas, rect, &*image, src_rect)?;
             }
+            Some(Background::Stretched(resource, s_rect)) => {
+                let image = resource_manager.image(resource);
+                canvas
+                    .copy(&image, s_rect.map(rect_to_rect2), Some(rect_to_rect2(rect)))
+                    .map_err(|e| format_err!("{}", e))?;
+            }
             None => {}
         }
         if let Some(ref text) = self.text {
@@ -0,0 +1,158 @@
+use crate::resources::messages::Messages;
+use crate::storage::DailyRecords;
+use crate::ui::common::{HorizontalAlign, Size};
+use crate::ui::component::dialog::*;
+use crate::ui::widget::container::Container;
+use crate::ui::widget::label::*;
+use crate::ui::widget::widget::*;
+use crate::util::date::Date;
+use crate::util::time::sec_to_str;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+
+const CELL_WIDTH: u32 = 44;
+const CELL_HEIGHT: u32 = 24;
+const CALENDAR_LEFT: u32 = 26;
+const CALENDAR_TOP: u32 = 85;
+
+fn create_calendar(
+    container: &mut Container<DialogResult<()>>,
+    records: &DailyRecords,
+    today: Date,
+    messages: &Messages,
+) {
+    for (i, weekday) in messages.weekdays.iter().enumerate() {
+        container.push(
+            CALENDAR_LEFT + i as u32 * CELL_WIDTH,
+            CALENDAR_TOP,
+            Label::new(
+                Size::new(CELL_WIDTH, CELL_HEIGHT),
+                weekday,
+                Color::RGB(255, 255, 0),
+                HorizontalAlign::Center,
+            )
+            .no_action(),
+        );
+    }
+
+    let first = today.first_of_month();
+    let offset = first.weekday();
+    for day in 0..first.days_in_month() {
+        let date = first.add_days(day as i64);
+        let cell = offset + day;
+        let color = if records.get(date).is_some() {
+            Color::RGB(0, 255, 0)
+        } else if date == today {
+            Color::RGB(255, 255, 0)
+        } else if date > today {
+            Color::RGB(128, 128, 128)
+        } else {
+            Color::RGB(255, 255, 255)
+        };
+        container.push(
+            CALENDAR_LEFT + (cell % 7) * CELL_WIDTH,
+            CALENDAR_TOP + (cell / 7 + 1) * CELL_HEIGHT,
+            Label::new(
+                Size::new(CELL_WIDTH, CELL_HEIGHT),
+                &date.day.to_string(),
+                color,
+                HorizontalAlign::Center,
+            )
+            .no_action(),
+        );
+    }
+}
+
+pub fn create_daily_dialog(
+    records: &DailyRecords,
+    today: Date,
+    messages: &Messages,
+    playable: bool,
+) -> Container<DialogResult<()>> {
+    let theme = DialogTheme::Blue;
+    let white = Color::RGB(255, 255, 255);
+
+    let mut container = dialog_container(Size::new(360, 420), theme);
+    container.push(
+        10,
+        10,
+        Label::title(Size::new(340, 40), messages.daily_puzzle).no_action(),
+    );
+    container.push(
+        10,
+        50,
+        Label::new(
+            Size::new(340, 26),
+            &format!(
+                "{} {}",
+                messages.months[today.month as usize - 1],
+                today.year
+            ),
+            white,
+            HorizontalAlign::Center,
+        )
+        .no_action(),
+    );
+
+    create_calendar(&mut container, records, today, messages);
+
+    let today_result = match records.get(today) {
+        Some(record) => sec_to_str(record.time),
+        None => messages.not_solved.to_string(),
+    };
+    let lines = [
+        format!("{} {}", messages.today, today_result),
+        format!(
+            "{} {}",
+            messages.current_streak,
+            records.current_streak(today)
+        ),
+        format!("{} {}", messages.best_streak, records.best_streak()),
+    ];
+    for (i, line) in lines.iter().enumerate() {
+        container.push(
+            26,
+            265 + i as u32 * 26,
+            Label::new(Size::new(308, 26), line, white, HorizontalAlign::Left).no_action(),
+        );
+    }
+
+    if playable {
+        container.push(
+            85,
+            380,
+            DialogButton::new(
+                Size::new(85, 25),
+                theme,
+                messages.play,
+                &[Keycode::Return],
+                DialogResult::Ok(()),
+            ),
+        );
+        container.push(
+            190,
+            380,
+            DialogButton::new(
+                Size::new(85, 25),
+                theme,
+                messages.close,
+                &[Keycode::Escape],
+                DialogResult::Cancel,
+            ),
+        );
+    } else {
+        container.push(
+            135,
+            380,
+            DialogButton::new(
+                Size::new(90, 25),
+                theme,
+                messages.ok,
+                &[Keycode::Escape, Keycode::Return],
+                DialogResult::Cancel,
+            ),
+        );
+    }
+
+    dialog_widget(None, container)
+}
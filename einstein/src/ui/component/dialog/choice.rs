@@ -0,0 +1,50 @@
+use super::theme::DialogTheme;
+use crate::ui::brick::*;
+use crate::ui::common::Size;
+use crate::ui::widget::choice::{Choice, ChoiceArrow};
+use crate::ui::widget::common::*;
+use sdl2::pixels::Color;
+
+pub fn dialog_choice(
+    theme: DialogTheme,
+    size: Size,
+    labels: Vec<String>,
+    selected: usize,
+) -> Choice {
+    Choice::new(
+        size,
+        labels,
+        selected,
+        move |size, label, highlighted, _resource_manager| {
+            let (color1, color2) = theme.colors3d();
+
+            let arrow = |arrow: ChoiceArrow, text: &str| {
+                let rect = Choice::arrow_rect(size, arrow);
+                Brick::new(rect.width, rect.height)
+                    .background(theme.background(highlighted == Some(arrow)))
+                    .border(Border::Etched(color1, color2))
+                    .text(
+                        Text::new(text)
+                            .font_size(FontSize::BUTTON)
+                            .color(Color::RGB(255, 255, 0))
+                            .shadow(),
+                    )
+            };
+
+            Brick::new(size.width, size.height)
+                .border(Border::Beveled(color2, color1))
+                .text(
+                    Text::new(label)
+                        .font_size(FontSize::TEXT)
+                        .color(Color::RGB(255, 255, 255))
+                        .shadow(),
+                )
+                .add(0, 0, arrow(ChoiceArrow::Prev, "<"))
+                .add(
+                    Choice::arrow_rect(size, ChoiceArrow::Next).left as u32,
+                    0,
+                    arrow(ChoiceArrow::Next, ">"),
+                )
+        },
+    )
+}
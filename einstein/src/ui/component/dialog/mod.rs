@@ -1,10 +1,12 @@
 pub mod button;
 pub mod checkbox;
+pub mod choice;
 pub mod slider;
 pub mod theme;
 
 pub use self::button::*;
 pub use self::checkbox::*;
+pub use self::choice::*;
 pub use self::slider::*;
 pub use self::theme::*;
 use crate::cell::RefCell;
@@ -1,66 +1,86 @@
 use crate::cell::RefCell;
 use crate::error::*;
+use crate::pool::{take_pooled, Pool};
 use crate::resources::audio::{APPLAUSE, GLASS};
 use crate::resources::manager::Resource;
 use crate::resources::messages::Messages;
 use crate::storage::*;
 use crate::ui::common::Size;
+use crate::ui::component::daily_dialog::create_daily_dialog;
 use crate::ui::component::dialog::{cond_dialog, DialogResult, DialogTheme};
 use crate::ui::component::failure_dialog::{new_failure_dialog, FailureChoice};
 use crate::ui::component::game_button::new_game_button;
 use crate::ui::component::game_title::GameTitle;
 use crate::ui::component::help_dialog::new_help_dialog;
+use crate::ui::component::loading_dialog::{new_loading_dialog, GameRequest, LoadingResult};
 use crate::ui::component::message_dialog::create_message_dialog;
 use crate::ui::component::options_dialog::new_options_dialog;
 use crate::ui::component::pause_dialog::new_pause_dialog;
 use crate::ui::component::player_name_dialog::new_player_name_dialog;
 use crate::ui::component::puzzle::puzzle::new_puzzle_widget;
 use crate::ui::component::puzzle::puzzle_cell::PuzzleAction;
-use crate::ui::component::rules_grid::{create_horizontal_rules, create_vertical_rules};
+use crate::ui::component::rules_grid::{
+    create_horizontal_rules, create_vertical_rules, RulesLayout, HORIZONTAL_RULES_SIZE,
+};
 use crate::ui::component::save_dialog::new_save_game_dialog;
 use crate::ui::component::topscores_dialog::create_topscores_dialog;
 use crate::ui::layout::grid::GridBuilder;
 use crate::ui::widget::common::*;
 use crate::ui::widget::container::Container;
 use crate::ui::widget::widget::*;
-use einstein_puzzle::puzzle_gen::generate_puzzle;
-use einstein_puzzle::rules::{apply, Possibilities, PuzzleSize, Rule, SolvedPuzzle};
-use rand::{thread_rng, Rng};
+use crate::util::date::Date;
+use einstein_puzzle::code::PuzzleCode;
+use einstein_puzzle::deduction::{next_deduction, Deduction};
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::puzzle_gen::{generate_puzzle_with_control, puzzle_rng, GenerationControl};
+use einstein_puzzle::rules::{
+    apply, arc_consistency, Change, Kind, Possibilities, Propagation, PuzzleSize, Rule,
+    SolvedPuzzle, Topology,
+};
+use rand::thread_rng;
 use sdl2::keyboard::Keycode;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
-fn generate_fittable_puzzle(
-    size: PuzzleSize,
-    rng: &mut impl Rng,
+// Bigger boards may not fit the rule panels for many attempts in a row.
+const MAX_FITTING_ATTEMPTS: usize = 50;
+
+pub fn generate_fittable_puzzle(
+    code: &PuzzleCode,
+    control: &mut GenerationControl,
 ) -> Result<(SolvedPuzzle, Vec<Rule>)> {
-    fn fits_into_ui(rules: &[Rule]) -> bool {
-        let mut horizontal = 0;
-        let mut vertical = 0;
-        for rule in rules {
-            match rule {
-                Rule::Near(..) | Rule::Between(..) | Rule::Direction(..) => horizontal += 1,
-                Rule::Under(..) => vertical += 1,
-                Rule::Open(..) => {}
-            }
-        }
+    fn fits_into_ui(size: PuzzleSize, rules: &[Rule]) -> bool {
+        let (vertical, horizontal): (Vec<&Rule>, Vec<&Rule>) = rules
+            .iter()
+            .filter(|rule| rule.is_non_trivial())
+            .partition(|rule| rule.is_vertical());
 
-        horizontal <= 24 && vertical <= 15
+        RulesLayout::for_size(size).fits(horizontal.len(), vertical.len())
     }
 
-    loop {
-        let (puzzle, rules) = generate_puzzle(size, rng)?;
-        if fits_into_ui(&rules) {
+    let mut rng = puzzle_rng(code.seed);
+    for _ in 0..MAX_FITTING_ATTEMPTS {
+        let (puzzle, rules) =
+            generate_puzzle_with_control(code.size, code.difficulty, &mut rng, control)?;
+        if fits_into_ui(code.size, &rules) {
             return Ok((puzzle, rules));
         }
     }
+    Err(format_err!("Cannot generate a puzzle that fits the screen"))
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug)]
 pub struct GamePrivate {
     pub solved_puzzle: SolvedPuzzle,
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    #[serde(default)]
+    pub code: Option<PuzzleCode>,
+    #[serde(default)]
+    pub daily: Option<Date>,
     pub rules: Vec<Rule>,
     pub possibilities: Possibilities,
     pub valid: bool,
@@ -76,19 +96,56 @@ pub struct GamePrivate {
     pub started: Option<Instant>,
 
     pub hinted: bool,
+    #[serde(skip)]
+    pub hint: Option<Deduction>,
 }
 
 const RAIN: Resource = resource!("./rain.bmp");
 
+const MIN_SIZE: u8 = 4;
+const MAX_SIZE: u8 = 8;
+// grids have two rows of houses
+const MAX_GRID_WIDTH: u8 = 4;
+const GRID_KINDS: u8 = 4;
+
 impl GamePrivate {
-    pub fn new() -> Result<Rc<RefCell<GamePrivate>>> {
-        let size = PuzzleSize {
-            kinds: 6,
-            values: 6,
-        };
-        let mut rng = thread_rng();
-        let (solved_puzzle, rules) = generate_fittable_puzzle(size, &mut rng)?;
+    pub fn supported_sizes() -> impl Iterator<Item = PuzzleSize> {
+        let grids = (2..=MAX_GRID_WIDTH).map(|width| PuzzleSize {
+            kinds: GRID_KINDS,
+            values: width * 2,
+            topology: Topology::Grid(width),
+        });
+        [Topology::Line, Topology::Ring]
+            .into_iter()
+            .flat_map(|topology| {
+                (MIN_SIZE..=MAX_SIZE).map(move |n| PuzzleSize {
+                    kinds: n,
+                    values: n,
+                    topology,
+                })
+            })
+            .chain(grids)
+    }
+
+    pub fn is_supported(code: &PuzzleCode) -> bool {
+        Self::supported_sizes().any(|size| size == code.size)
+    }
+
+    pub fn daily_code(date: Date) -> Result<PuzzleCode> {
+        let seed = date.year as u64 * 10000 + date.month as u64 * 100 + date.day as u64;
+        Ok(PuzzleCode::new(
+            PuzzleSize::default(),
+            Difficulty::Medium,
+            seed,
+        )?)
+    }
+
+    pub fn generate(code: PuzzleCode, control: &mut GenerationControl) -> Result<GamePrivate> {
+        let (solved_puzzle, rules) = generate_fittable_puzzle(&code, control)?;
+        Ok(Self::from_puzzle(code, solved_puzzle, rules))
+    }
 
+    pub fn from_puzzle(code: PuzzleCode, solved_puzzle: SolvedPuzzle, rules: Vec<Rule>) -> Self {
         let mut possibilities = Possibilities::new(solved_puzzle.size());
         for rule in &rules {
             if let Rule::Open(..) = *rule {
@@ -99,17 +156,21 @@ impl GamePrivate {
         let mut vertical_rules = Vec::new();
         let mut horizontal_rules = Vec::new();
         for (index, rule) in rules.iter().enumerate() {
-            match *rule {
-                Rule::Under(..) => vertical_rules.push(index),
-                Rule::Near(..) | Rule::Between(..) | Rule::Direction(..) => {
-                    horizontal_rules.push(index)
-                }
-                _ => {}
+            if !rule.is_non_trivial() {
+                continue;
+            }
+            if rule.is_vertical() {
+                vertical_rules.push(index);
+            } else {
+                horizontal_rules.push(index);
             }
         }
 
-        Ok(Rc::new(RefCell::new(GamePrivate {
+        GamePrivate {
             solved_puzzle,
+            difficulty: code.difficulty,
+            code: Some(code),
+            daily: None,
             rules,
             possibilities,
             valid: true,
@@ -121,11 +182,20 @@ impl GamePrivate {
             excluded: HashSet::new(),
             show_excluded: false,
             hinted: false,
-        })))
+            hint: None,
+        }
+    }
+
+    pub fn score_table(&self) -> ScoreTable {
+        ScoreTable {
+            size: self.solved_puzzle.size(),
+            difficulty: self.difficulty,
+        }
     }
 
     pub fn restart(&mut self) {
-        let mut possibilities = Possibilities::new(self.solved_puzzle.size());
+        let mut possibilities = Possibilities::new(self.solved_puzzle.size())
+            .with_propagation(self.possibilities.propagation());
         for rule in &self.rules {
             if let Rule::Open(..) = *rule {
                 possibilities = apply(&possibilities, rule);
@@ -135,9 +205,55 @@ impl GamePrivate {
         self.excluded.clear();
         self.show_excluded = false;
         self.hinted = true;
+        self.hint = None;
         self.reset();
     }
 
+    pub fn set_propagation(&mut self, propagation: Propagation) {
+        // the board solving itself does not deserve a score
+        if propagation > Propagation::Singles {
+            self.hinted = true;
+        }
+        self.possibilities = self.possibilities.with_propagation(propagation);
+        self.deduce();
+    }
+
+    /// Deductions across rows the player asked the board to do on its own.
+    pub fn deduce(&mut self) {
+        if self.possibilities.propagation() >= Propagation::ArcConsistency {
+            self.possibilities = arc_consistency(&self.possibilities, &self.rules);
+        }
+    }
+
+    pub fn hint(&mut self) -> bool {
+        let Some(deduction) = next_deduction(&self.possibilities, &self.rules) else {
+            return false;
+        };
+        if let Some(index) = self.rules.iter().position(|rule| *rule == deduction.rule) {
+            self.excluded.remove(&index);
+            self.show_excluded = false;
+        }
+        self.hint = Some(deduction);
+        self.hinted = true;
+        true
+    }
+
+    pub fn is_hinted_rule(&self, index: usize) -> bool {
+        self.hint
+            .as_ref()
+            .is_some_and(|hint| self.rules.get(index) == Some(&hint.rule))
+    }
+
+    pub fn hinted_change(&self, col: u8, row: Kind) -> Option<Change> {
+        let change = self.hint.as_ref()?.eliminations.first()?.change;
+        match change {
+            Change::Exclude(c, thing) | Change::Set(c, thing) if c == col && thing.row == row => {
+                Some(change)
+            }
+            _ => None,
+        }
+    }
+
     pub fn is_valid(&self) -> bool {
         self.possibilities.is_valid(&self.solved_puzzle)
     }
@@ -208,6 +324,7 @@ fn game_popup_background<A: 'static>(
 enum MenuAction {
     Pause,
     ToggleExcluded,
+    Hint,
     Exit,
     Save,
     Options,
@@ -215,8 +332,8 @@ enum MenuAction {
 }
 
 fn make_game_menu(messages: &'static Messages) -> Container<MenuAction> {
-    let container = Container::container(Size::new(308, 70), None, None);
-    GridBuilder::new(container, 3, 2)
+    let container = Container::container(Size::new(308, 100), None, None);
+    GridBuilder::new(container, 3, 3)
         .add(
             0,
             0,
@@ -239,11 +356,13 @@ fn make_game_menu(messages: &'static Messages) -> Container<MenuAction> {
             new_game_button(messages.options, &[], MenuAction::Options),
         )
         .add(2, 1, new_game_button(messages.help, &[], MenuAction::Help))
+        .add(1, 2, new_game_button(messages.hint, &[], MenuAction::Hint))
         .build()
 }
 
 pub fn new_game_widget(
     storage: Rc<RefCell<Storage>>,
+    pool: Arc<Mutex<Pool>>,
     state: Rc<RefCell<GamePrivate>>,
     messages: &'static Messages,
 ) -> Container<()> {
@@ -254,7 +373,10 @@ pub fn new_game_widget(
     let victory_trigger = Rc::new(RefCell::new(None));
     let save_score_trigger = Rc::new(RefCell::new(None));
     let show_scores_trigger = Rc::new(RefCell::new(None));
+    let show_daily_trigger = Rc::new(RefCell::new(None));
     let failure_trigger = Rc::new(RefCell::new(None));
+    let loading_trigger = Rc::new(RefCell::new(None));
+    let generation_error_trigger = Rc::new(RefCell::new(None));
 
     let mut container = Container::<()>::screen_modal(Background::Image(&RAIN, None));
 
@@ -290,18 +412,19 @@ pub fn new_game_widget(
         })
     });
 
+    let rules_layout = RulesLayout::for_size(state.borrow().solved_puzzle.size());
     container.push(
-        348,
+        800 - 12 - HORIZONTAL_RULES_SIZE.width,
         68,
-        create_horizontal_rules(Size::new(800 - 348 - 12, 412), state.clone()).no_action(),
+        create_horizontal_rules(rules_layout, state.clone(), messages).no_action(),
     );
     container.push(
         12,
         495,
-        create_vertical_rules(Size::new(800 - 12 * 2, 48 * 2), state.clone()).no_action(),
+        create_vertical_rules(rules_layout, state.clone(), messages).no_action(),
     );
 
-    container.push(12, 400, {
+    container.push(12, 388, {
         let this_state = state.clone();
         let pause_trigger2 = pause_trigger.clone();
         let save_game_trigger2 = save_game_trigger.clone();
@@ -317,6 +440,13 @@ pub fn new_game_widget(
                 this_state.borrow_mut().toggle_show_excluded();
                 Ok(EventReaction::empty())
             }
+            MenuAction::Hint => {
+                if this_state.borrow_mut().hint() {
+                    Ok(EventReaction::update())
+                } else {
+                    Ok(EventReaction::empty())
+                }
+            }
             MenuAction::Exit => Ok(EventReaction::action(())),
             MenuAction::Save => {
                 this_state.borrow_mut().stop();
@@ -378,12 +508,16 @@ pub fn new_game_widget(
                 DialogResult::Ok(ref options) => {
                     storage2.borrow_mut().fullscreen = options.fullscreen;
                     storage2.borrow_mut().volume = options.volume;
+                    storage2.borrow_mut().propagation = options.propagation;
+                    this_state2
+                        .borrow_mut()
+                        .set_propagation(options.propagation);
                     // screen->setMode(VideoMode(800, 600, 24, options.fullscreen));
                     context.audio().set_volume(options.volume);
                 }
                 DialogResult::Cancel => {}
             }
-            Ok(EventReaction::empty())
+            Ok(EventReaction::update())
         })
     });
 
@@ -417,13 +551,23 @@ pub fn new_game_widget(
     container.push(0, 0, {
         let save_score_trigger2 = save_score_trigger.clone();
         let show_scores_trigger2 = show_scores_trigger.clone();
+        let show_daily_trigger2 = show_daily_trigger.clone();
         let storage2 = storage.clone();
+        let this_state = state.clone();
         cond_dialog(&victory_trigger, move |score| {
             let score = *score;
             create_message_dialog(DialogTheme::White, messages.won).map_action(move |_| score)
         })
         .flat_map_action(move |score, _| {
-            if let Some(score) = score.filter(|score| storage2.borrow().scores.is_deserving(*score))
+            let table = this_state.borrow().score_table();
+            if let Some(date) = this_state.borrow().daily {
+                // games won with hints or restarts do not count for the day
+                if let Some(time) = *score {
+                    storage2.borrow_mut().daily.record(date, time);
+                }
+                *show_daily_trigger2.borrow_mut() = Some(date);
+            } else if let Some(score) =
+                score.filter(|score| storage2.borrow().scores.is_deserving(table, *score))
             {
                 *save_score_trigger2.borrow_mut() = Some(score);
             } else {
@@ -437,6 +581,7 @@ pub fn new_game_widget(
         let show_scores_trigger2 = show_scores_trigger.clone();
         let storage1 = storage.clone();
         let storage2 = storage.clone();
+        let this_state = state.clone();
         cond_dialog(&save_score_trigger, move |score| {
             let score = *score;
             let last_name = match storage1.borrow().last_name {
@@ -451,6 +596,8 @@ pub fn new_game_widget(
             let pos = storage2.borrow_mut().scores.add_score_entry(Score {
                 name: name.to_string(),
                 score: *score,
+                difficulty: this_state.borrow().difficulty,
+                size: this_state.borrow().solved_puzzle.size(),
             });
             *show_scores_trigger2.borrow_mut() = Some(pos);
             Ok(EventReaction::empty())
@@ -459,20 +606,43 @@ pub fn new_game_widget(
 
     container.push(0, 0, {
         let storage2 = storage.clone();
+        let this_state = state.clone();
         cond_dialog(&show_scores_trigger, move |index| {
-            create_topscores_dialog(&storage2.borrow().scores, messages, *index)
+            let table = this_state.borrow().score_table();
+            create_topscores_dialog(&storage2.borrow().scores, table, messages, *index)
+        })
+        .no_action()
+    });
+
+    container.push(0, 0, {
+        let storage2 = storage.clone();
+        cond_dialog(&show_daily_trigger, move |date| {
+            create_daily_dialog(&storage2.borrow().daily, *date, messages, false)
         })
         .no_action()
     });
 
     container.push(0, 0, {
         let state2 = state.clone();
+        let loading_trigger2 = loading_trigger.clone();
         cond_dialog(&failure_trigger, move |_| new_failure_dialog(messages)).flat_map_action(
             move |result, _| match *result {
                 FailureChoice::StartNew => {
-                    let g = GamePrivate::new().unwrap();
-                    *state2.borrow_mut() = g.borrow().clone();
-                    Ok(EventReaction::empty())
+                    let size = state2.borrow().solved_puzzle.size();
+                    let difficulty = state2.borrow().difficulty;
+                    if let Some(puzzle) = take_pooled(&pool, size, difficulty) {
+                        let propagation = state2.borrow().possibilities.propagation();
+                        *state2.borrow_mut() = GamePrivate::from_puzzle(
+                            puzzle.code,
+                            puzzle.solved_puzzle,
+                            puzzle.rules,
+                        );
+                        state2.borrow_mut().set_propagation(propagation);
+                    } else {
+                        let code = PuzzleCode::random(size, difficulty, &mut thread_rng())?;
+                        *loading_trigger2.borrow_mut() = Some(GameRequest { code, daily: None });
+                    }
+                    Ok(EventReaction::update())
                 }
                 FailureChoice::TryAgain => {
                     state2.borrow_mut().restart();
@@ -483,5 +653,42 @@ pub fn new_game_widget(
         )
     });
 
+    container.push(0, 0, {
+        let state2 = state.clone();
+        let failure_trigger2 = failure_trigger.clone();
+        let generation_error_trigger2 = generation_error_trigger.clone();
+        cond_dialog(&loading_trigger, move |request| {
+            new_loading_dialog(*request, messages)
+        })
+        .flat_map_action(move |result, _| {
+            match *result {
+                LoadingResult::Loaded(ref game) => {
+                    let propagation = state2.borrow().possibilities.propagation();
+                    *state2.borrow_mut() = game.borrow().clone();
+                    state2.borrow_mut().set_propagation(propagation);
+                }
+                LoadingResult::Failed => {
+                    *generation_error_trigger2.borrow_mut() = Some(());
+                }
+                // back to the choice the player made it from
+                LoadingResult::Cancelled => {
+                    *failure_trigger2.borrow_mut() = Some(());
+                }
+            }
+            Ok(EventReaction::update())
+        })
+    });
+
+    container.push(0, 0, {
+        let failure_trigger2 = failure_trigger.clone();
+        cond_dialog(&generation_error_trigger, move |_| {
+            create_message_dialog(DialogTheme::Red, messages.generation_failed)
+        })
+        .flat_map_action(move |_, _| {
+            *failure_trigger2.borrow_mut() = Some(());
+            Ok(EventReaction::update())
+        })
+    });
+
     container
 }
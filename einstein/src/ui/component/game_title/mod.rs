@@ -11,16 +11,16 @@ use never::Never;
 use sdl2::pixels::Color;
 use std::cell::Cell;
 use std::rc::Rc;
-use std::time::Duration;
 
 const GAME_TITLE: Resource = resource!("./title.bmp");
 const PADDING: u32 = 7;
 const WATCH_WIDTH: u32 = 100;
+const CODE_WIDTH: u32 = 190;
 
 pub struct GameTitle {
     title: String,
     state: Rc<RefCell<GamePrivate>>,
-    last_duration: Cell<Option<Duration>>,
+    last_secs: Cell<Option<u64>>,
 }
 
 impl GameTitle {
@@ -28,7 +28,7 @@ impl GameTitle {
         GameTitle {
             title: title.to_owned(),
             state,
-            last_duration: Cell::new(None),
+            last_secs: Cell::new(None),
         }
     }
 }
@@ -44,7 +44,9 @@ impl Widget<Never> for GameTitle {
     fn on_event(&mut self, event: &Event, _context: &dyn Context) -> EventResult<Never> {
         match *event {
             Event::Tick => {
-                if Some(self.state.borrow().get_current_duration()) != self.last_duration.get() {
+                // the clock only shows whole seconds
+                let secs = self.state.borrow().get_current_duration().as_secs();
+                if Some(secs) != self.last_secs.get() {
                     Ok(EventReaction::update())
                 } else {
                     Ok(EventReaction::empty())
@@ -56,10 +58,10 @@ impl Widget<Never> for GameTitle {
 
     fn draw(&self, _resource_manager: &dyn ResourceManager) -> Brick {
         let duration = self.state.borrow().get_current_duration();
-        self.last_duration.set(Some(duration));
+        self.last_secs.set(Some(duration.as_secs()));
         let s = sec_to_str(duration.as_secs() as u32);
 
-        Brick::new(self.get_size().width, self.get_size().height)
+        let mut brick = Brick::new(self.get_size().width, self.get_size().height)
             .background(Background::Image(&GAME_TITLE, None))
             .text(
                 Text::new(&self.title)
@@ -79,6 +81,20 @@ impl Widget<Never> for GameTitle {
                             .color(Color::RGB(255, 255, 255))
                             .halign(HorizontalAlign::Right),
                     ),
-            )
+            );
+        if let Some(code) = self.state.borrow().code {
+            brick.push(
+                PADDING,
+                PADDING,
+                Brick::new(CODE_WIDTH, self.get_size().height - (2 * PADDING)).text(
+                    Text::new(code.to_string())
+                        .font_size(FontSize::TEXT)
+                        .color(Color::RGB(255, 255, 255))
+                        .shadow()
+                        .halign(HorizontalAlign::Left),
+                ),
+            );
+        }
+        brick
     }
 }
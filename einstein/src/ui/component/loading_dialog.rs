@@ -0,0 +1,167 @@
+use crate::cell::RefCell;
+use crate::error::*;
+use crate::resources::manager::ResourceManager;
+use crate::resources::messages::Messages;
+use crate::ui::brick::*;
+use crate::ui::common::{HorizontalAlign, Size};
+use crate::ui::component::dialog::*;
+use crate::ui::component::game::GamePrivate;
+use crate::ui::context::Context;
+use crate::ui::widget::common::*;
+use crate::ui::widget::container::Container;
+use crate::ui::widget::label::*;
+use crate::ui::widget::widget::*;
+use crate::util::date::Date;
+use einstein_puzzle::code::PuzzleCode;
+use einstein_puzzle::puzzle_gen::{GenerationControl, Progress};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+const GENERATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Copy)]
+pub struct GameRequest {
+    pub code: PuzzleCode,
+    pub daily: Option<Date>,
+}
+
+#[derive(Clone)]
+pub enum LoadingResult {
+    Loaded(Rc<RefCell<GamePrivate>>),
+    Failed,
+    Cancelled,
+}
+
+/// Generates the game on a worker thread and shows how far it got.
+struct Generation {
+    size: Size,
+    receiver: Receiver<Result<GamePrivate>>,
+    progress: Arc<Mutex<Progress>>,
+    cancel: Arc<AtomicBool>,
+    shown: Cell<Progress>,
+}
+
+impl Generation {
+    fn start(size: Size, request: GameRequest) -> Self {
+        let (sender, receiver) = channel();
+        let progress = Arc::new(Mutex::new(Progress::default()));
+        let cancel = Arc::new(AtomicBool::new(false));
+
+        let progress2 = progress.clone();
+        let cancel2 = cancel.clone();
+        thread::spawn(move || {
+            let mut control = GenerationControl::new()
+                .with_timeout(GENERATION_TIMEOUT)
+                .with_cancel(&cancel2)
+                .with_progress(|progress| *progress2.lock().unwrap() = progress);
+            let game = GamePrivate::generate(request.code, &mut control).map(|mut game| {
+                game.daily = request.daily;
+                game
+            });
+            // nobody listens if the dialog is already closed
+            let _ = sender.send(game);
+        });
+
+        Self {
+            size,
+            receiver,
+            progress,
+            cancel,
+            shown: Cell::new(Progress::default()),
+        }
+    }
+}
+
+impl Drop for Generation {
+    fn drop(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+    }
+}
+
+impl Widget<LoadingResult> for Generation {
+    fn get_size(&self) -> Size {
+        self.size
+    }
+
+    fn on_event(&mut self, event: &Event, _context: &dyn Context) -> EventResult<LoadingResult> {
+        let Event::Tick = *event else {
+            return Ok(EventReaction::empty());
+        };
+        match self.receiver.try_recv() {
+            Ok(Ok(game)) => Ok(EventReaction::update_and_action(LoadingResult::Loaded(
+                Rc::new(RefCell::new(game)),
+            ))),
+            Ok(Err(_)) | Err(TryRecvError::Disconnected) => {
+                Ok(EventReaction::update_and_action(LoadingResult::Failed))
+            }
+            Err(TryRecvError::Empty) => {
+                if *self.progress.lock().unwrap() != self.shown.get() {
+                    Ok(EventReaction::update())
+                } else {
+                    Ok(EventReaction::empty())
+                }
+            }
+        }
+    }
+
+    fn draw(&self, _resource_manager: &dyn ResourceManager) -> Brick {
+        let progress = *self.progress.lock().unwrap();
+        self.shown.set(progress);
+        let text = if progress.attempt == 0 {
+            String::new()
+        } else {
+            format!(
+                "{}:  +{}  -{}",
+                progress.attempt, progress.rules_added, progress.rules_removed
+            )
+        };
+        Brick::new(self.size.width, self.size.height).text(
+            Text::new(&text)
+                .font_size(FontSize::TEXT)
+                .color(Color::RGB(255, 255, 255))
+                .shadow()
+                .halign(HorizontalAlign::Center),
+        )
+    }
+}
+
+pub fn new_loading_dialog(
+    request: GameRequest,
+    messages: &'static Messages,
+) -> Container<LoadingResult> {
+    let theme = DialogTheme::Green;
+
+    let container = dialog_container(Size::new(400, 130), theme)
+        .add(
+            0,
+            15,
+            Label::new(
+                Size::new(400, 30),
+                messages.loading,
+                Color::RGB(255, 255, 0),
+                HorizontalAlign::Center,
+            )
+            .no_action(),
+        )
+        .add(0, 50, Generation::start(Size::new(400, 26), request))
+        .add(
+            160,
+            90,
+            DialogButton::new(
+                Size::new(80, 25),
+                theme,
+                messages.cancel,
+                &[Keycode::Escape],
+                LoadingResult::Cancelled,
+            ),
+        );
+
+    dialog_widget(None, container)
+}
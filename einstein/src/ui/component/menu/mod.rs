@@ -1,15 +1,21 @@
 use crate::cell::RefCell;
 use crate::error::*;
+use crate::pool::{take_pooled, Pool, PoolRefill};
 use crate::resources::manager::Resource;
 use crate::resources::messages::Messages;
 use crate::storage::*;
 use crate::ui::common::{HorizontalAlign, Size};
 use crate::ui::component::about_dialog::create_about_dialog;
+use crate::ui::component::daily_dialog::create_daily_dialog;
 use crate::ui::component::dialog::*;
 use crate::ui::component::game::{new_game_widget, GamePrivate};
 use crate::ui::component::help_dialog::new_help_dialog;
 use crate::ui::component::load_dialog::new_load_game_dialog;
+use crate::ui::component::loading_dialog::{new_loading_dialog, GameRequest, LoadingResult};
+use crate::ui::component::message_dialog::create_message_dialog;
 use crate::ui::component::options_dialog::new_options_dialog;
+use crate::ui::component::puzzle_code_dialog::new_puzzle_code_dialog;
+use crate::ui::component::start_game_dialog::{new_start_game_dialog, NewGameOptions};
 use crate::ui::component::topscores_dialog::create_topscores_dialog;
 use crate::ui::context::MainLoopQuit;
 use crate::ui::layout::grid::GridBuilder;
@@ -18,15 +24,21 @@ use crate::ui::widget::container::Container;
 use crate::ui::widget::label::*;
 use crate::ui::widget::menu_button::*;
 use crate::ui::widget::widget::*;
+use crate::util::date::Date;
+use einstein_puzzle::code::PuzzleCode;
+use rand::thread_rng;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use std::rc::Rc;
+use std::sync::{Arc, Mutex};
 
 const MENU_BG: Resource = resource!("./nova.bmp");
 
 #[derive(Clone)]
 enum MainMenuAction {
     NewGame,
+    EnterCode,
+    DailyPuzzle,
     LoadGame,
     ShowScores,
     Help,
@@ -38,8 +50,14 @@ enum MainMenuAction {
 pub fn make_menu(
     messages: &'static Messages,
     storage: Rc<RefCell<Storage>>,
+    pool: Arc<Mutex<Pool>>,
 ) -> Result<Container<MainLoopQuit>> {
     let new_game_trigger = Rc::new(RefCell::new(None));
+    let loading_trigger = Rc::new(RefCell::new(None));
+    let generation_error_trigger = Rc::new(RefCell::new(None));
+    let start_game_trigger = Rc::new(RefCell::new(None));
+    let enter_code_trigger = Rc::new(RefCell::new(None));
+    let show_daily_trigger = Rc::new(RefCell::new(None));
     let load_game_trigger = Rc::new(RefCell::new(None));
     let show_scores_trigger = Rc::new(RefCell::new(None));
     let show_help_trigger = Rc::new(RefCell::new(None));
@@ -67,14 +85,22 @@ pub fn make_menu(
         .no_action(),
     );
 
-    container.push(550, 340, {
-        let new_game_trigger2 = new_game_trigger.clone();
+    container.push(
+        0,
+        0,
+        PoolRefill::new(pool.clone(), storage.clone()).no_action(),
+    );
+
+    container.push(550, 290, {
+        let start_game_trigger2 = start_game_trigger.clone();
+        let enter_code_trigger2 = enter_code_trigger.clone();
+        let show_daily_trigger2 = show_daily_trigger.clone();
         let load_game_trigger2 = load_game_trigger.clone();
         let show_scores_trigger2 = show_scores_trigger.clone();
         let show_help_trigger2 = show_help_trigger.clone();
         let show_opts_trigger2 = show_opts_trigger.clone();
         let show_about_trigger2 = show_about_trigger.clone();
-        GridBuilder::new(Container::container(Size::new(220, 210), None, None), 1, 7)
+        GridBuilder::new(Container::container(Size::new(220, 270), None, None), 1, 9)
             .add(
                 0,
                 0,
@@ -88,6 +114,26 @@ pub fn make_menu(
             .add(
                 0,
                 1,
+                new_menu_button(
+                    Size::new(220, 30),
+                    messages.enter_code,
+                    &[],
+                    MainMenuAction::EnterCode,
+                ),
+            )
+            .add(
+                0,
+                2,
+                new_menu_button(
+                    Size::new(220, 30),
+                    messages.daily_puzzle,
+                    &[],
+                    MainMenuAction::DailyPuzzle,
+                ),
+            )
+            .add(
+                0,
+                3,
                 new_menu_button(
                     Size::new(220, 30),
                     messages.load_game,
@@ -97,7 +143,7 @@ pub fn make_menu(
             )
             .add(
                 0,
-                2,
+                4,
                 new_menu_button(
                     Size::new(220, 30),
                     messages.top_scores,
@@ -107,7 +153,7 @@ pub fn make_menu(
             )
             .add(
                 0,
-                3,
+                5,
                 new_menu_button(
                     Size::new(220, 30),
                     messages.rules,
@@ -117,7 +163,7 @@ pub fn make_menu(
             )
             .add(
                 0,
-                4,
+                6,
                 new_menu_button(
                     Size::new(220, 30),
                     messages.options,
@@ -127,7 +173,7 @@ pub fn make_menu(
             )
             .add(
                 0,
-                5,
+                7,
                 new_menu_button(
                     Size::new(220, 30),
                     messages.about,
@@ -137,7 +183,7 @@ pub fn make_menu(
             )
             .add(
                 0,
-                6,
+                8,
                 new_menu_button(
                     Size::new(220, 30),
                     messages.exit,
@@ -148,8 +194,15 @@ pub fn make_menu(
             .build()
             .flat_map_action(move |menu_action, _| match menu_action {
                 MainMenuAction::NewGame => {
-                    let game = GamePrivate::new().unwrap();
-                    *new_game_trigger2.borrow_mut() = Some(game);
+                    *start_game_trigger2.borrow_mut() = Some(());
+                    Ok(EventReaction::empty())
+                }
+                MainMenuAction::EnterCode => {
+                    *enter_code_trigger2.borrow_mut() = Some(());
+                    Ok(EventReaction::empty())
+                }
+                MainMenuAction::DailyPuzzle => {
+                    *show_daily_trigger2.borrow_mut() = Some(Date::today());
                     Ok(EventReaction::empty())
                 }
                 MainMenuAction::LoadGame => {
@@ -176,11 +229,120 @@ pub fn make_menu(
             })
     });
 
+    container.push(0, 0, {
+        let storage1 = storage.clone();
+        let storage2 = storage.clone();
+        let pool2 = pool.clone();
+        let new_game_trigger2 = new_game_trigger.clone();
+        let loading_trigger2 = loading_trigger.clone();
+        cond_dialog(&start_game_trigger, move |_| {
+            let options = NewGameOptions {
+                size: storage1.borrow().size,
+                difficulty: storage1.borrow().difficulty,
+            };
+            new_start_game_dialog(options, messages)
+        })
+        .flat_map_action(move |result, _| {
+            match *result {
+                DialogResult::Ok(ref options) => {
+                    storage2.borrow_mut().size = options.size;
+                    storage2.borrow_mut().difficulty = options.difficulty;
+                    if let Some(puzzle) = take_pooled(&pool2, options.size, options.difficulty) {
+                        let game = GamePrivate::from_puzzle(
+                            puzzle.code,
+                            puzzle.solved_puzzle,
+                            puzzle.rules,
+                        );
+                        *new_game_trigger2.borrow_mut() = Some(Rc::new(RefCell::new(game)));
+                    } else {
+                        let code = PuzzleCode::random(
+                            options.size,
+                            options.difficulty,
+                            &mut thread_rng(),
+                        )?;
+                        *loading_trigger2.borrow_mut() = Some(GameRequest { code, daily: None });
+                    }
+                }
+                DialogResult::Cancel => {}
+            }
+            Ok(EventReaction::empty())
+        })
+    });
+
+    container.push(0, 0, {
+        let loading_trigger2 = loading_trigger.clone();
+        cond_dialog(&enter_code_trigger, move |_| {
+            new_puzzle_code_dialog(messages)
+        })
+        .flat_map_action(move |result, _| {
+            match *result {
+                DialogResult::Ok(code) => {
+                    *loading_trigger2.borrow_mut() = Some(GameRequest { code, daily: None });
+                }
+                DialogResult::Cancel => {}
+            }
+            Ok(EventReaction::empty())
+        })
+    });
+
+    container.push(0, 0, {
+        let storage2 = storage.clone();
+        let loading_trigger2 = loading_trigger.clone();
+        cond_dialog(&show_daily_trigger, move |today| {
+            let today = *today;
+            create_daily_dialog(&storage2.borrow().daily, today, messages, true)
+                .map_action(move |result| result.map(|_| today))
+        })
+        .flat_map_action(move |result, _| {
+            match *result {
+                DialogResult::Ok(today) => {
+                    *loading_trigger2.borrow_mut() = Some(GameRequest {
+                        code: GamePrivate::daily_code(today)?,
+                        daily: Some(today),
+                    });
+                }
+                DialogResult::Cancel => {}
+            }
+            Ok(EventReaction::empty())
+        })
+    });
+
+    container.push(0, 0, {
+        let new_game_trigger2 = new_game_trigger.clone();
+        let generation_error_trigger2 = generation_error_trigger.clone();
+        cond_dialog(&loading_trigger, move |request| {
+            new_loading_dialog(*request, messages)
+        })
+        .flat_map_action(move |result, _| {
+            match *result {
+                LoadingResult::Loaded(ref game) => {
+                    *new_game_trigger2.borrow_mut() = Some(game.clone());
+                }
+                LoadingResult::Failed => {
+                    *generation_error_trigger2.borrow_mut() = Some(());
+                }
+                LoadingResult::Cancelled => {}
+            }
+            Ok(EventReaction::update())
+        })
+    });
+
+    container.push(
+        0,
+        0,
+        cond_dialog(&generation_error_trigger, move |_| {
+            create_message_dialog(DialogTheme::Red, messages.generation_failed)
+        })
+        .no_action(),
+    );
+
     container.push(0, 0, {
         let storage2 = storage.clone();
         cond_dialog(&new_game_trigger, move |game| {
+            game.borrow_mut()
+                .set_propagation(storage2.borrow().propagation);
             game.borrow_mut().start();
-            new_game_widget(storage2.clone(), game.clone(), messages)
+            new_game_widget(storage2.clone(), pool.clone(), game.clone(), messages)
         })
         .no_action()
     });
@@ -207,7 +369,12 @@ pub fn make_menu(
     container.push(0, 0, {
         let storage2 = storage.clone();
         cond_dialog(&show_scores_trigger, move |_| {
-            create_topscores_dialog(&storage2.borrow().scores, messages, None)
+            let storage = storage2.borrow();
+            let table = ScoreTable {
+                size: storage.size,
+                difficulty: storage.difficulty,
+            };
+            create_topscores_dialog(&storage.scores, table, messages, None)
         })
         .no_action()
     });
@@ -229,6 +396,7 @@ pub fn make_menu(
                 DialogResult::Ok(ref options) => {
                     storage2.borrow_mut().fullscreen = options.fullscreen;
                     storage2.borrow_mut().volume = options.volume;
+                    storage2.borrow_mut().propagation = options.propagation;
                     // screen->setMode(VideoMode(800, 600, 24, options.fullscreen));
                     context.audio().set_volume(options.volume);
                 }
@@ -1,4 +1,5 @@
 pub mod about_dialog;
+pub mod daily_dialog;
 pub mod dialog;
 pub mod failure_dialog;
 pub mod game;
@@ -7,13 +8,16 @@ pub mod game_name_dialog;
 pub mod game_title;
 pub mod help_dialog;
 pub mod load_dialog;
+pub mod loading_dialog;
 pub mod menu;
 pub mod message_dialog;
 pub mod options_dialog;
 pub mod pause_dialog;
 pub mod player_name_dialog;
 pub mod puzzle;
+pub mod puzzle_code_dialog;
 pub mod rule;
 pub mod rules_grid;
 pub mod save_dialog;
+pub mod start_game_dialog;
 pub mod topscores_dialog;
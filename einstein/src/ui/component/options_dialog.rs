@@ -6,6 +6,7 @@ use crate::ui::component::dialog::*;
 use crate::ui::widget::container::Container;
 use crate::ui::widget::label::*;
 use crate::ui::widget::widget::*;
+use einstein_puzzle::rules::Propagation;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
 use std::rc::Rc;
@@ -15,6 +16,7 @@ pub struct Options {
     pub fullscreen: bool,
     pub volume: u32,
     volume_float: f32,
+    pub propagation: Propagation,
 }
 
 pub fn new_options_dialog(
@@ -27,9 +29,10 @@ pub fn new_options_dialog(
         fullscreen: storage.fullscreen,
         volume: storage.volume,
         volume_float: storage.volume as f32 / 100_f32,
+        propagation: storage.propagation,
     }));
 
-    let mut container = dialog_container(Size::new(300, 260), theme);
+    let mut container = dialog_container(Size::new(300, 300), theme);
 
     container.push(
         0,
@@ -75,7 +78,35 @@ pub fn new_options_dialog(
             },
         )
     });
-    container.push(65, 220, {
+    container.push(
+        15,
+        200,
+        Label::new(
+            Size::new(120, 26),
+            messages.auto_deduction,
+            Color::RGB(255, 255, 255),
+            HorizontalAlign::Left,
+        )
+        .no_action(),
+    );
+    container.push(135, 200, {
+        let state2 = state.clone();
+        let labels = Propagation::ALL
+            .iter()
+            .map(|propagation| messages.propagation_name(*propagation).to_string())
+            .collect();
+        let selected = Propagation::ALL
+            .iter()
+            .position(|propagation| *propagation == state.borrow().propagation)
+            .unwrap_or_default();
+        dialog_choice(theme, Size::new(150, 26), labels, selected).flat_map_action(
+            move |index, _| {
+                state2.borrow_mut().propagation = Propagation::ALL[*index];
+                Ok(EventReaction::empty())
+            },
+        )
+    });
+    container.push(65, 260, {
         let state2 = state.clone();
         DialogButton::new(
             Size::new(85, 25),
@@ -91,7 +122,7 @@ pub fn new_options_dialog(
     });
     container.push(
         155,
-        220,
+        260,
         DialogButton::new(
             Size::new(85, 25),
             theme,
@@ -1,26 +1,102 @@
 use crate::cell::RefCell;
+use crate::resources::manager::ResourceManager;
+use crate::ui::brick::*;
 use crate::ui::common::Size;
 use crate::ui::component::game::GamePrivate;
 use crate::ui::component::puzzle::puzzle_cell::{PuzzleAction, PuzzleCell};
 use crate::ui::layout::grid::GridBuilder;
+use crate::ui::widget::common::*;
 use crate::ui::widget::container::Container;
-use einstein_puzzle::rules::{Kind, PuzzleSize};
+use crate::ui::widget::widget::*;
+use einstein_puzzle::rules::{Kind, Topology};
+use never::Never;
+use sdl2::pixels::Color;
 use std::rc::Rc;
 
 const WIDTH: u32 = 308;
 const HEIGHT: u32 = 308;
+const MAX_TILE: u32 = 48;
+const GAP: u32 = 4;
+const RING_MARK_HEIGHT: u32 = 12;
+const BAND_GAP: u32 = 12;
 
-pub fn new_puzzle_widget(state: &Rc<RefCell<GamePrivate>>) -> Container<PuzzleAction> {
-    let container = Container::container(Size::new(WIDTH, HEIGHT), None, None);
-    let PuzzleSize { kinds, values } = state.borrow().solved_puzzle.size();
-    let mut grid = GridBuilder::new(container, usize::from(values), usize::from(kinds));
-    for row in 0..kinds {
-        let kind = Kind(row);
-        for col in 0..values {
-            let cell = PuzzleCell::new(state, kind, col);
-            grid = grid.add(usize::from(col), usize::from(row), cell);
-        }
+/// Bracket under the field joining the first and the last columns of a round table.
+struct RingMark {
+    size: Size,
+    tile: u32,
+}
+
+impl Widget<Never> for RingMark {
+    fn get_size(&self) -> Size {
+        self.size
     }
 
-    grid.build()
+    fn draw(&self, _resource_manager: &dyn ResourceManager) -> Brick {
+        let line = |width, height| {
+            Brick::new(width, height).background(Background::Color(Color::RGB(255, 255, 0)))
+        };
+        let left = self.tile / 2 - 1;
+        let right = self.size.width - self.tile / 2 - 1;
+        let bottom = self.size.height - 4;
+        Brick::new(self.size.width, self.size.height)
+            .add(left, 2, line(2, bottom))
+            .add(right, 2, line(2, bottom))
+            .add(left, bottom, line(right - left + 2, 2))
+    }
+}
+
+pub fn new_puzzle_widget(state: &Rc<RefCell<GamePrivate>>) -> Container<PuzzleAction> {
+    let size = state.borrow().solved_puzzle.size();
+    let mark_height = match size.topology {
+        Topology::Ring => RING_MARK_HEIGHT,
+        _ => 0,
+    };
+    // every row of houses of a grid puzzle gets its own band of kinds
+    let (kinds32, width32) = (u32::from(size.kinds), u32::from(size.width()));
+    let bands = u32::from(size.grid_rows());
+    let tile = u32::min(
+        (WIDTH + GAP) / width32 - GAP,
+        (HEIGHT - mark_height - (bands - 1) * BAND_GAP + GAP) / (kinds32 * bands) - GAP,
+    )
+    .min(MAX_TILE);
+    let band_size = Size::new(width32 * (tile + GAP) - GAP, kinds32 * (tile + GAP) - GAP);
+    let mut band_widgets: Vec<Container<PuzzleAction>> = (0..size.grid_rows())
+        .map(|band| {
+            let container = Container::container(band_size, None, None);
+            let mut grid = GridBuilder::new(
+                container,
+                usize::from(size.width()),
+                usize::from(size.kinds),
+            );
+            for row in 0..size.kinds {
+                let kind = Kind(row);
+                for x in 0..size.width() {
+                    let cell = PuzzleCell::new(state, kind, band * size.width() + x, tile);
+                    grid = grid.add(usize::from(x), usize::from(row), cell);
+                }
+            }
+            grid.build()
+        })
+        .collect();
+
+    if size.topology == Topology::Line {
+        return band_widgets.pop().unwrap();
+    }
+    let fields_height = bands * (band_size.height + BAND_GAP) - BAND_GAP;
+    let mut container = Container::container(
+        Size::new(band_size.width, fields_height + mark_height),
+        None,
+        None,
+    );
+    for (band, widget) in band_widgets.into_iter().enumerate() {
+        container.push(0, band as u32 * (band_size.height + BAND_GAP), widget);
+    }
+    if mark_height > 0 {
+        let mark = RingMark {
+            size: Size::new(band_size.width, mark_height),
+            tile,
+        };
+        container.push(0, fields_height, mark.no_action());
+    }
+    container
 }
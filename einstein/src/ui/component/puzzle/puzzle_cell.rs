@@ -2,8 +2,7 @@ use crate::cell::RefCell;
 use crate::resources::audio::LASER;
 use crate::resources::manager::ResourceManager;
 use crate::resources::thing::{
-    get_small_thing_rect, get_thing_rect, EMPTY_TILE, LARGE_THINGS_ATLAS,
-    LARGE_THINGS_ATLAS_HIGHLIGHTED, SMALL_THINGS_ATLAS, SMALL_THINGS_ATLAS_HIGHLIGHTED,
+    draw_large_thing, draw_small_thing, EMPTY_TILE, EMPTY_TILE_HIGHLIGHTED,
 };
 use crate::ui::brick::*;
 use crate::ui::common::{Rect, Size};
@@ -11,14 +10,11 @@ use crate::ui::component::game::GamePrivate;
 use crate::ui::context::Context;
 use crate::ui::widget::common::*;
 use crate::ui::widget::widget::*;
-use einstein_puzzle::rules::{Kind, Thing, Value};
+use einstein_puzzle::rules::{Change, Kind, Thing, Value};
 use sdl2::mouse::MouseButton;
 use std::cell::Cell;
 use std::rc::Rc;
 
-const FIELD_TILE_WIDTH: u32 = 48;
-const FIELD_TILE_HEIGHT: u32 = 48;
-
 #[derive(Clone)]
 pub enum PuzzleAction {
     Victory,
@@ -29,15 +25,17 @@ pub struct PuzzleCell {
     state: Rc<RefCell<GamePrivate>>,
     row: Kind,
     col: u8,
+    tile: u32,
     highlighted: Cell<Option<Option<Value>>>,
 }
 
 impl PuzzleCell {
-    pub fn new(state: &Rc<RefCell<GamePrivate>>, row: Kind, col: u8) -> Self {
+    pub fn new(state: &Rc<RefCell<GamePrivate>>, row: Kind, col: u8, tile: u32) -> Self {
         Self {
             state: state.clone(),
             row,
             col,
+            tile,
             highlighted: Cell::new(None),
         }
     }
@@ -51,16 +49,19 @@ impl PuzzleCell {
         arr
     }
 
+    fn choice_grid(&self) -> (u32, u32) {
+        let values = self.state.borrow().solved_puzzle.size().values as u32;
+        let cols = (1..).find(|cols| cols * cols >= values).unwrap_or(1);
+        (cols, values.div_ceil(cols))
+    }
+
     fn local_choice_cell_rect(&self, Value(value): Value) -> Rect {
-        let x: i32 = ((value % 3) as i32) * ((FIELD_TILE_WIDTH / 3) as i32);
-        let y: i32 = ((value / 3) as i32) * ((FIELD_TILE_HEIGHT / 3) as i32)
-            + ((FIELD_TILE_HEIGHT / 6) as i32);
-        Rect::new(
-            x,
-            y,
-            (FIELD_TILE_WIDTH / 3) as u32,
-            (FIELD_TILE_HEIGHT / 3) as u32,
-        )
+        let (cols, rows) = self.choice_grid();
+        let choice_size = self.tile / cols;
+        let value = value as u32;
+        let x = (value % cols) * choice_size;
+        let y = (value / cols) * choice_size + (self.tile - rows * choice_size) / 2;
+        Rect::new(x as i32, y as i32, choice_size, choice_size)
     }
 
     fn local_find_choice(&self, x: i32, y: i32) -> Option<Value> {
@@ -116,6 +117,8 @@ impl PuzzleCell {
                         .possibilities
                         .set(self.col, self.row, thing.value);
                     self.state.borrow_mut().possibilities = p;
+                    self.state.borrow_mut().deduce();
+                    self.state.borrow_mut().hint = None;
                     context
                         .audio()
                         .play(&*context.resource_manager().chunk(&LASER))
@@ -135,6 +138,8 @@ impl PuzzleCell {
                             .possibilities
                             .exclude(self.col, self.row, thing.value);
                     self.state.borrow_mut().possibilities = p;
+                    self.state.borrow_mut().deduce();
+                    self.state.borrow_mut().hint = None;
                     context
                         .audio()
                         .play(&*context.resource_manager().chunk(&LASER))
@@ -183,8 +188,8 @@ impl PuzzleCell {
 impl Widget<PuzzleAction> for PuzzleCell {
     fn get_size(&self) -> Size {
         Size {
-            width: FIELD_TILE_WIDTH as u32,
-            height: FIELD_TILE_HEIGHT as u32,
+            width: self.tile,
+            height: self.tile,
         }
     }
 
@@ -208,23 +213,30 @@ impl Widget<PuzzleAction> for PuzzleCell {
         let row = self.row;
         let col = self.col;
 
-        let mut brick = Brick::new(FIELD_TILE_WIDTH, FIELD_TILE_HEIGHT);
+        let mut brick = Brick::new(self.tile, self.tile);
 
         if let Some(value) = self.state.borrow().possibilities.get_defined(col, row) {
             let thing = Thing { row, value };
             let highlight = self.highlighted.get() == Some(None);
+            brick.push(
+                0,
+                0,
+                draw_large_thing(thing, self.tile, self.tile, highlight),
+            );
+        } else {
+            let hinted_value = match self.state.borrow().hinted_change(col, row) {
+                Some(Change::Exclude(_, thing)) | Some(Change::Set(_, thing)) => Some(thing.value),
+                None => None,
+            };
 
-            let rect = get_thing_rect(thing);
-            brick = brick.background(Background::Image(
-                if highlight {
-                    &LARGE_THINGS_ATLAS_HIGHLIGHTED
+            brick = brick.background(Background::Stretched(
+                if hinted_value.is_some() {
+                    &EMPTY_TILE_HIGHLIGHTED
                 } else {
-                    &LARGE_THINGS_ATLAS
+                    &EMPTY_TILE
                 },
-                Some(rect),
+                None,
             ));
-        } else {
-            brick = brick.background(Background::Image(&EMPTY_TILE, None));
 
             for value in self.all_values() {
                 let choice_rect = self.local_choice_cell_rect(value);
@@ -235,22 +247,13 @@ impl Widget<PuzzleAction> for PuzzleCell {
                     .possibilities
                     .is_possible(col as u8, thing)
                 {
-                    let highlight = self.highlighted.get() == Some(Some(value));
+                    let highlight =
+                        self.highlighted.get() == Some(Some(value)) || hinted_value == Some(value);
 
-                    let rect = get_small_thing_rect(thing);
                     brick.push(
                         choice_rect.left as u32,
                         choice_rect.top as u32,
-                        Brick::new(choice_rect.width, choice_rect.height).background(
-                            Background::Image(
-                                if highlight {
-                                    &SMALL_THINGS_ATLAS_HIGHLIGHTED
-                                } else {
-                                    &SMALL_THINGS_ATLAS
-                                },
-                                Some(rect),
-                            ),
-                        ),
+                        draw_small_thing(thing, choice_rect.width, choice_rect.height, highlight),
                     );
                 }
             }
@@ -0,0 +1,90 @@
+use crate::cell::RefCell;
+use crate::resources::messages::Messages;
+use crate::ui::common::{HorizontalAlign, Size};
+use crate::ui::component::dialog::*;
+use crate::ui::component::game::GamePrivate;
+use crate::ui::widget::conditional::ConditionalWidget;
+use crate::ui::widget::container::Container;
+use crate::ui::widget::input_field::*;
+use crate::ui::widget::label::*;
+use crate::ui::widget::widget::*;
+use einstein_puzzle::code::PuzzleCode;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use std::rc::Rc;
+
+pub fn new_puzzle_code_dialog(messages: &'static Messages) -> Container<DialogResult<PuzzleCode>> {
+    let theme = DialogTheme::Blue;
+    let yellow = Color::RGB(255, 255, 0);
+
+    let state = Rc::new(RefCell::new(String::new()));
+    let error_trigger = Rc::new(RefCell::new(None));
+
+    let container = dialog_container(Size::new(460, 130), theme)
+        .add(
+            10,
+            20,
+            Label::new(
+                Size::new(150, 26),
+                messages.puzzle_code,
+                yellow,
+                HorizontalAlign::Left,
+            )
+            .no_action(),
+        )
+        .add(170, 20, {
+            let state2 = state.clone();
+            let error_trigger2 = error_trigger.clone();
+            InputField::new(Size::new(280, 26), "", 24).flat_map_action(move |code, _| {
+                *state2.borrow_mut() = code.to_string();
+                *error_trigger2.borrow_mut() = None;
+                Ok(EventReaction::update())
+            })
+        })
+        .add(
+            10,
+            55,
+            ConditionalWidget::new(error_trigger.clone(), move |_| {
+                Label::new(
+                    Size::new(440, 26),
+                    messages.invalid_puzzle_code,
+                    Color::RGB(255, 96, 96),
+                    HorizontalAlign::Center,
+                )
+                .no_action()
+            }),
+        )
+        .add(140, 90, {
+            let state2 = state.clone();
+            let error_trigger2 = error_trigger.clone();
+            DialogButton::new(
+                Size::new(80, 25),
+                theme,
+                messages.ok,
+                &[Keycode::Return],
+                (),
+            )
+            .flat_map_action(move |_, _| match state2.borrow().parse::<PuzzleCode>() {
+                Ok(code) if GamePrivate::is_supported(&code) => {
+                    Ok(EventReaction::action(DialogResult::Ok(code)))
+                }
+                _ => {
+                    *error_trigger2.borrow_mut() = Some(());
+                    Ok(EventReaction::update())
+                }
+            })
+        })
+        .add(
+            230,
+            90,
+            DialogButton::new(
+                Size::new(80, 25),
+                theme,
+                messages.cancel,
+                &[Keycode::Escape],
+                DialogResult::Cancel,
+            ),
+        );
+
+    dialog_widget(None, container)
+}
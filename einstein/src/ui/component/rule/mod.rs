@@ -2,18 +2,17 @@ use crate::cell::RefCell;
 use crate::error::format_err;
 use crate::resources::audio::WHIZZ;
 use crate::resources::manager::{Resource, ResourceManager};
-use crate::resources::thing::{
-    get_thing_rect, EMPTY_TILE, EMPTY_TILE_HIGHLIGHTED, LARGE_THINGS_ATLAS,
-    LARGE_THINGS_ATLAS_HIGHLIGHTED,
-};
+use crate::resources::messages::Messages;
+use crate::resources::thing::{draw_large_thing, EMPTY_TILE, EMPTY_TILE_HIGHLIGHTED};
 use crate::ui::brick::*;
-use crate::ui::common::Size;
+use crate::ui::common::{Size, VerticalAlign};
 use crate::ui::component::game::GamePrivate;
 use crate::ui::context::Context;
 use crate::ui::widget::common::*;
 use crate::ui::widget::widget::*;
-use einstein_puzzle::rules::{Rule, Thing};
+use einstein_puzzle::rules::{Clause, Junction, Rule};
 use sdl2::mouse::MouseButton;
+use sdl2::pixels::Color;
 use std::cell::Cell;
 use std::rc::Rc;
 
@@ -29,18 +28,80 @@ const HINT_SIDE_ICON_HIGHLIGHTED: Resource = resource!("./hint-side-h.bmp");
 const HINT_BETWEEN_ICON: Resource = resource!("./betwarr.bmp");
 const HINT_BETWEEN_ICON_HIGHLIGHTED: Resource = resource!("./betwarr-h.bmp");
 
-fn draw_thing(thing: Thing, highlighted: bool) -> Brick {
-    Brick::new(TILE_WIDTH, TILE_HEIGHT).background(Background::Image(
-        if highlighted {
-            &LARGE_THINGS_ATLAS_HIGHLIGHTED
-        } else {
-            &LARGE_THINGS_ATLAS
-        },
-        Some(get_thing_rect(thing)),
-    ))
+fn draw_icon(icon: &'static Resource, width: u32, height: u32) -> Brick {
+    Brick::new(width, height).background(Background::Stretched(icon, None))
+}
+
+// A red cross laid over a hint to turn it into its negation.
+fn draw_cross(width: u32, height: u32, highlighted: bool) -> Brick {
+    Brick::new(width, height).text(
+        Text::new("×")
+            .font_size(FontSize((width.min(height) * 3 / 4).max(8) as u16))
+            .color(if highlighted {
+                Color::RGB(255, 128, 128)
+            } else {
+                Color::RGB(255, 0, 0)
+            })
+            .shadow(),
+    )
+}
+
+// Number of columns written above a hint icon.
+fn draw_distance(distance: u8, width: u32, height: u32, highlighted: bool) -> Brick {
+    Brick::new(width, height).text(
+        Text::new(distance)
+            .font_size(FontSize((height / 3).max(8) as u16))
+            .color(if highlighted {
+                Color::RGB(255, 255, 128)
+            } else {
+                Color::RGB(255, 255, 0)
+            })
+            .valign(VerticalAlign::Top)
+            .shadow(),
+    )
+}
+
+// Arrow or bar standing for a relation between houses of a grid.
+fn draw_symbol(symbol: &str, tile: u32, highlighted: bool) -> Brick {
+    Brick::new(tile, tile).text(
+        Text::new(symbol)
+            .font_size(FontSize((tile * 2 / 3).max(8) as u16))
+            .color(if highlighted {
+                Color::RGB(255, 255, 128)
+            } else {
+                Color::RGB(255, 255, 0)
+            })
+            .shadow(),
+    )
+}
+
+fn draw_column(col: u8, tile: u32) -> Brick {
+    Brick::new(tile, tile).text(
+        Text::new(col + 1)
+            .font_size(FontSize((tile / 2).max(8) as u16))
+            .color(Color::RGB(255, 255, 255))
+            .shadow(),
+    )
+}
+
+fn draw_clause(clause: Clause, tile: u32, highlighted: bool, messages: &Messages) -> Brick {
+    match clause {
+        Clause::At(col, thing) => Brick::new(tile * 2, tile)
+            .add(0, 0, draw_large_thing(thing, tile, tile, highlighted))
+            .add(tile, 0, draw_column(col, tile)),
+        _ => draw_rule(&clause.rule(), tile, highlighted, messages),
+    }
+}
+
+fn clause_size(clause: Clause, tile: u32) -> (u32, u32) {
+    match clause {
+        Clause::At(..) => (tile * 2, tile),
+        Clause::Under(..) => (tile, tile * 2),
+        Clause::Near(..) | Clause::LeftOf(..) => (tile * 3, tile),
+    }
 }
 
-fn draw_rule(rule: &Rule, highlighted: bool) -> Brick {
+fn draw_rule(rule: &Rule, tile: u32, highlighted: bool, messages: &Messages) -> Brick {
     let bg = Background::Image(
         if highlighted {
             &EMPTY_TILE_HIGHLIGHTED
@@ -49,80 +110,212 @@ fn draw_rule(rule: &Rule, highlighted: bool) -> Brick {
         },
         None,
     );
+    let thing = |thing| draw_large_thing(thing, tile, tile, highlighted);
     match *rule {
-        Rule::Near(thing1, thing2) => Brick::new(TILE_WIDTH * 3, TILE_HEIGHT)
+        Rule::Near(thing1, thing2) => Brick::new(tile * 3, tile)
             .background(bg)
-            .add(0, 0, draw_thing(thing1, highlighted))
+            .add(0, 0, thing(thing1))
             .add(
-                TILE_WIDTH,
+                tile,
                 0,
-                Brick::new(TILE_WIDTH, TILE_HEIGHT).background(Background::Image(
+                draw_icon(
                     if highlighted {
                         &HINT_NEAR_ICON_HIGHLIGHTED
                     } else {
                         &HINT_NEAR_ICON
                     },
-                    None,
-                )),
+                    tile,
+                    tile,
+                ),
             )
-            .add(TILE_WIDTH * 2, 0, draw_thing(thing2, highlighted)),
-        Rule::Direction(thing1, thing2) => Brick::new(TILE_WIDTH * 3, TILE_HEIGHT)
+            .add(tile * 2, 0, thing(thing2)),
+        Rule::Direction(thing1, thing2) => Brick::new(tile * 3, tile)
             .background(bg)
-            .add(0, 0, draw_thing(thing1, highlighted))
-            .add(TILE_WIDTH * 2, 0, draw_thing(thing2, highlighted))
+            .add(0, 0, thing(thing1))
+            .add(tile * 2, 0, thing(thing2))
             .add(
-                TILE_WIDTH,
+                tile,
                 0,
-                Brick::new(TILE_WIDTH, TILE_HEIGHT).background(Background::Image(
+                draw_icon(
                     if highlighted {
                         &HINT_SIDE_ICON_HIGHLIGHTED
                     } else {
                         &HINT_SIDE_ICON
                     },
-                    None,
-                )),
+                    tile,
+                    tile,
+                ),
             ),
-        Rule::Under(thing1, thing2) => Brick::new(TILE_WIDTH, TILE_HEIGHT * 2)
+        Rule::Under(thing1, thing2) => Brick::new(tile, tile * 2)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(0, tile, thing(thing2)),
+        Rule::Between(thing1, thing2, thing3) => {
+            let (arrow_width, arrow_height) = (70 * tile / TILE_WIDTH, 15 * tile / TILE_HEIGHT);
+            Brick::new(tile * 3, tile)
+                .add(0, 0, thing(thing1))
+                .add(tile, 0, thing(thing2))
+                .add(tile * 2, 0, thing(thing3))
+                .add(
+                    (3 * tile - arrow_width) / 2,
+                    0,
+                    draw_icon(
+                        if highlighted {
+                            &HINT_BETWEEN_ICON_HIGHLIGHTED
+                        } else {
+                            &HINT_BETWEEN_ICON
+                        },
+                        arrow_width,
+                        arrow_height,
+                    ),
+                )
+        }
+        Rule::NotNear(thing1, thing2) => Brick::new(tile * 3, tile)
             .background(bg)
-            .add(0, 0, draw_thing(thing1, highlighted))
-            .add(0, TILE_HEIGHT, draw_thing(thing2, highlighted)),
-        Rule::Between(thing1, thing2, thing3) => Brick::new(TILE_WIDTH * 3, TILE_HEIGHT)
-            .add(0, 0, draw_thing(thing1, highlighted))
-            .add(TILE_WIDTH, 0, draw_thing(thing2, highlighted))
-            .add(TILE_WIDTH * 2, 0, draw_thing(thing3, highlighted))
+            .add(0, 0, thing(thing1))
             .add(
-                (3 * TILE_WIDTH - 70) / 2,
+                tile,
                 0,
-                Brick::new(70, 15).background(Background::Image(
+                draw_icon(
                     if highlighted {
-                        &HINT_BETWEEN_ICON_HIGHLIGHTED
+                        &HINT_NEAR_ICON_HIGHLIGHTED
                     } else {
-                        &HINT_BETWEEN_ICON
+                        &HINT_NEAR_ICON
                     },
-                    None,
-                )),
-            ),
-        _ => Brick::new(0, 0),
+                    tile,
+                    tile,
+                ),
+            )
+            .add(tile, 0, draw_cross(tile, tile, highlighted))
+            .add(tile * 2, 0, thing(thing2)),
+        Rule::NotUnder(thing1, thing2) => Brick::new(tile, tile * 2)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(0, tile, thing(thing2))
+            .add(0, tile / 2, draw_cross(tile, tile, highlighted)),
+        Rule::NotAt(col, thing1) => Brick::new(tile * 3, tile)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(tile, 0, draw_cross(tile, tile, highlighted))
+            .add(tile * 2, 0, draw_column(col, tile)),
+        Rule::LeftOf(thing1, thing2) => Brick::new(tile * 3, tile)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(tile * 2, 0, thing(thing2))
+            .add(
+                tile,
+                0,
+                draw_icon(
+                    if highlighted {
+                        &HINT_SIDE_ICON_HIGHLIGHTED
+                    } else {
+                        &HINT_SIDE_ICON
+                    },
+                    tile,
+                    tile,
+                ),
+            )
+            .add(tile, 0, draw_distance(1, tile, tile, highlighted)),
+        Rule::Distance(thing1, thing2, distance) => Brick::new(tile * 3, tile)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(tile * 2, 0, thing(thing2))
+            .add(
+                tile,
+                0,
+                draw_icon(
+                    if highlighted {
+                        &HINT_NEAR_ICON_HIGHLIGHTED
+                    } else {
+                        &HINT_NEAR_ICON
+                    },
+                    tile,
+                    tile,
+                ),
+            )
+            .add(tile, 0, draw_distance(distance, tile, tile, highlighted)),
+        Rule::Either(clause1, clause2, junction) => {
+            // both clauses are drawn at a smaller scale under the conjunction
+            let label_height = tile / 4;
+            let small = tile * 3 / 8;
+            let place = |clause| {
+                let (width, height) = clause_size(clause, small);
+                let top = label_height + (tile - label_height).saturating_sub(height) / 2;
+                (
+                    width,
+                    top,
+                    draw_clause(clause, small, highlighted, messages),
+                )
+            };
+            let (_, top1, brick1) = place(clause1);
+            let (width2, top2, brick2) = place(clause2);
+            let label = match junction {
+                Junction::Inclusive => messages.clause_or,
+                Junction::Exclusive => messages.clause_either,
+            };
+            Brick::new(tile * 3, tile)
+                .background(bg)
+                .add(
+                    0,
+                    0,
+                    Brick::new(tile * 3, label_height).text(
+                        Text::new(label)
+                            .font_size(FontSize(label_height.max(8) as u16))
+                            .color(Color::RGB(255, 255, 0))
+                            .shadow(),
+                    ),
+                )
+                .add(0, top1, brick1)
+                .add(tile * 3 - width2, top2, brick2)
+        }
+        Rule::Above(thing1, thing2) => Brick::new(tile * 3, tile)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(tile, 0, draw_symbol("↓", tile, highlighted))
+            .add(tile * 2, 0, thing(thing2)),
+        Rule::SameRow(thing1, thing2) => Brick::new(tile * 3, tile)
+            .background(bg)
+            .add(0, 0, thing(thing1))
+            .add(tile, 0, draw_symbol("═", tile, highlighted))
+            .add(tile * 2, 0, thing(thing2)),
+        Rule::Open(..) => Brick::new(0, 0),
     }
 }
 
 pub struct RuleWidget {
     size: Size,
+    tile: u32,
     state: Rc<RefCell<GamePrivate>>,
     index: Option<usize>,
     highlighted: Cell<bool>,
+    messages: &'static Messages<'static>,
 }
 
 impl RuleWidget {
-    pub fn new(size: Size, state: Rc<RefCell<GamePrivate>>, index: Option<usize>) -> Self {
+    pub fn new(
+        size: Size,
+        tile: u32,
+        state: Rc<RefCell<GamePrivate>>,
+        index: Option<usize>,
+        messages: &'static Messages,
+    ) -> Self {
         RuleWidget {
             size,
+            tile,
             state,
             index,
             highlighted: Cell::new(false),
+            messages,
         }
     }
 
+    fn is_highlighted(&self) -> bool {
+        self.highlighted.get()
+            || self
+                .index
+                .is_some_and(|index| self.state.borrow().is_hinted_rule(index))
+    }
+
     fn get_rule(&self) -> Option<Rule> {
         let index = self.index?;
         let rule = *self.state.borrow().rules.get(index)?;
@@ -177,10 +370,10 @@ impl Widget<usize> for RuleWidget {
 
     fn draw(&self, _resource_manager: &dyn ResourceManager) -> Brick {
         if let Some(rule) = self.get_rule() {
-            draw_rule(&rule, self.highlighted.get())
+            draw_rule(&rule, self.tile, self.is_highlighted(), self.messages)
         } else {
             Brick::new(self.size.width, self.size.height).background(Background::Image(
-                if self.highlighted.get() {
+                if self.is_highlighted() {
                     &EMPTY_TILE_HIGHLIGHTED
                 } else {
                     &EMPTY_TILE
@@ -1,33 +1,101 @@
 use crate::cell::RefCell;
+use crate::resources::messages::Messages;
 use crate::ui::common::Size;
 use crate::ui::component::game::GamePrivate;
 use crate::ui::component::rule::{RuleWidget, TILE_HEIGHT, TILE_WIDTH};
 use crate::ui::layout::grid::GridBuilder;
 use crate::ui::widget::container::Container;
+use einstein_puzzle::rules::PuzzleSize;
 use std::rc::Rc;
 
-pub fn create_horizontal_rules(size: Size, state: Rc<RefCell<GamePrivate>>) -> Container<usize> {
-    let container = Container::container(size, None, None);
-    let mut grid = GridBuilder::new(container, 3, 8);
-    let rule_size = Size::new(TILE_WIDTH * 3, TILE_HEIGHT);
-    for i in 0..24 {
+pub const HORIZONTAL_RULES_SIZE: Size = Size::new(440, 412);
+pub const VERTICAL_RULES_SIZE: Size = Size::new(776, 96);
+
+const GAP: u32 = 4;
+const SMALL_TILE: u32 = 32;
+
+fn capacity(area: Size, rule: Size) -> (usize, usize) {
+    (
+        ((area.width + GAP) / (rule.width + GAP)) as usize,
+        ((area.height + GAP) / (rule.height + GAP)) as usize,
+    )
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct RulesLayout {
+    tile: u32,
+}
+
+impl RulesLayout {
+    fn horizontal_rule_size(self) -> Size {
+        Size::new(self.tile * 3, self.tile * TILE_HEIGHT / TILE_WIDTH)
+    }
+
+    fn vertical_rule_size(self) -> Size {
+        Size::new(self.tile, self.tile * TILE_HEIGHT / TILE_WIDTH * 2)
+    }
+
+    fn horizontal_capacity(self) -> (usize, usize) {
+        capacity(HORIZONTAL_RULES_SIZE, self.horizontal_rule_size())
+    }
+
+    fn vertical_capacity(self) -> (usize, usize) {
+        capacity(VERTICAL_RULES_SIZE, self.vertical_rule_size())
+    }
+
+    // Up to 6x6 the classic 24 + 15 slots are enough, bigger boards need smaller tiles.
+    pub fn for_size(size: PuzzleSize) -> Self {
+        let tile = if size.kinds.max(size.values) <= 6 {
+            TILE_WIDTH
+        } else {
+            SMALL_TILE
+        };
+        RulesLayout { tile }
+    }
+
+    pub fn fits(self, horizontal: usize, vertical: usize) -> bool {
+        let (cols, rows) = self.horizontal_capacity();
+        let (vcols, vrows) = self.vertical_capacity();
+        horizontal <= cols * rows && vertical <= vcols * vrows
+    }
+}
+
+pub fn create_horizontal_rules(
+    layout: RulesLayout,
+    state: Rc<RefCell<GamePrivate>>,
+    messages: &'static Messages,
+) -> Container<usize> {
+    let container = Container::container(HORIZONTAL_RULES_SIZE, None, None);
+    let (cols, rows) = layout.horizontal_capacity();
+    let mut grid = GridBuilder::new(container, cols, rows);
+    let rule_size = layout.horizontal_rule_size();
+    for i in 0..cols * rows {
         let rule_index = state.borrow().horizontal_rules.get(i).copied();
         grid = grid.add(
-            i % 3,
-            i / 3,
-            RuleWidget::new(rule_size, state.clone(), rule_index),
+            i % cols,
+            i / cols,
+            RuleWidget::new(rule_size, layout.tile, state.clone(), rule_index, messages),
         );
     }
     grid.build()
 }
 
-pub fn create_vertical_rules(size: Size, state: Rc<RefCell<GamePrivate>>) -> Container<usize> {
-    let container = Container::container(size, None, None);
-    let mut grid = GridBuilder::new(container, 15, 1);
-    let rule_size = Size::new(TILE_WIDTH, TILE_HEIGHT * 2);
-    for i in 0..15 {
+pub fn create_vertical_rules(
+    layout: RulesLayout,
+    state: Rc<RefCell<GamePrivate>>,
+    messages: &'static Messages,
+) -> Container<usize> {
+    let container = Container::container(VERTICAL_RULES_SIZE, None, None);
+    let (cols, rows) = layout.vertical_capacity();
+    let mut grid = GridBuilder::new(container, cols, rows);
+    let rule_size = layout.vertical_rule_size();
+    for i in 0..cols * rows {
         let rule_index = state.borrow().vertical_rules.get(i).copied();
-        grid = grid.add(i, 0, RuleWidget::new(rule_size, state.clone(), rule_index));
+        grid = grid.add(
+            i % cols,
+            i / cols,
+            RuleWidget::new(rule_size, layout.tile, state.clone(), rule_index, messages),
+        );
     }
     grid.build()
 }
@@ -0,0 +1,135 @@
+use crate::cell::RefCell;
+use crate::resources::messages::Messages;
+use crate::ui::common::{HorizontalAlign, Size};
+use crate::ui::component::dialog::*;
+use crate::ui::component::game::GamePrivate;
+use crate::ui::widget::container::Container;
+use crate::ui::widget::label::*;
+use crate::ui::widget::widget::*;
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::rules::{PuzzleSize, Topology};
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use std::rc::Rc;
+
+#[derive(Clone)]
+pub struct NewGameOptions {
+    pub size: PuzzleSize,
+    pub difficulty: Difficulty,
+}
+
+pub fn size_name(size: PuzzleSize, messages: &Messages) -> String {
+    match size.topology {
+        Topology::Line => format!("{}×{}", size.values, size.kinds),
+        Topology::Ring => format!("{}×{}, {}", size.values, size.kinds, messages.round_table),
+        Topology::Grid(width) => format!(
+            "{}×{}, {} {}×{}",
+            size.values,
+            size.kinds,
+            messages.grid,
+            width,
+            size.grid_rows()
+        ),
+    }
+}
+
+pub fn new_start_game_dialog(
+    options: NewGameOptions,
+    messages: &'static Messages,
+) -> Container<DialogResult<NewGameOptions>> {
+    let theme = DialogTheme::Blue;
+
+    let state = Rc::new(RefCell::new(options));
+
+    let mut container = dialog_container(Size::new(360, 195), theme);
+
+    container.push(
+        0,
+        5,
+        Label::title(Size::new(360, 40), messages.new_game).no_action(),
+    );
+    container.push(
+        15,
+        65,
+        Label::new(
+            Size::new(130, 26),
+            messages.board_size,
+            Color::RGB(255, 255, 255),
+            HorizontalAlign::Left,
+        )
+        .no_action(),
+    );
+    container.push(150, 65, {
+        let state2 = state.clone();
+        let sizes: Vec<PuzzleSize> = GamePrivate::supported_sizes().collect();
+        let labels = sizes
+            .iter()
+            .map(|size| size_name(*size, messages))
+            .collect();
+        let selected = sizes
+            .iter()
+            .position(|size| *size == state.borrow().size)
+            .unwrap_or_default();
+        dialog_choice(theme, Size::new(195, 26), labels, selected).flat_map_action(
+            move |index, _| {
+                state2.borrow_mut().size = sizes[*index];
+                Ok(EventReaction::empty())
+            },
+        )
+    });
+    container.push(
+        15,
+        105,
+        Label::new(
+            Size::new(130, 26),
+            messages.difficulty,
+            Color::RGB(255, 255, 255),
+            HorizontalAlign::Left,
+        )
+        .no_action(),
+    );
+    container.push(150, 105, {
+        let state2 = state.clone();
+        let labels = Difficulty::ALL
+            .iter()
+            .map(|difficulty| messages.difficulty_name(*difficulty).to_string())
+            .collect();
+        let selected = Difficulty::ALL
+            .iter()
+            .position(|difficulty| *difficulty == state.borrow().difficulty)
+            .unwrap_or_default();
+        dialog_choice(theme, Size::new(195, 26), labels, selected).flat_map_action(
+            move |index, _| {
+                state2.borrow_mut().difficulty = Difficulty::ALL[*index];
+                Ok(EventReaction::empty())
+            },
+        )
+    });
+    container.push(85, 155, {
+        let state2 = state.clone();
+        DialogButton::new(
+            Size::new(85, 25),
+            theme,
+            messages.ok,
+            &[Keycode::Return],
+            (),
+        )
+        .flat_map_action(move |_, _| {
+            let options: NewGameOptions = state2.borrow().clone();
+            Ok(EventReaction::action(DialogResult::Ok(options)))
+        })
+    });
+    container.push(
+        190,
+        155,
+        DialogButton::new(
+            Size::new(85, 25),
+            theme,
+            messages.cancel,
+            &[Keycode::Escape],
+            DialogResult::Cancel,
+        ),
+    );
+
+    dialog_widget(None, container)
+}
@@ -1,86 +1,171 @@
+use crate::cell::RefCell;
 use crate::resources::messages::Messages;
-use crate::storage::Scores;
+use crate::storage::{Score, ScoreTable, Scores};
 use crate::ui::common::{HorizontalAlign, Size};
 use crate::ui::component::dialog::*;
+use crate::ui::component::game::GamePrivate;
+use crate::ui::component::start_game_dialog::size_name;
 use crate::ui::layout::grid::GridBuilder;
+use crate::ui::widget::conditional::ConditionalWidget;
 use crate::ui::widget::container::Container;
 use crate::ui::widget::label::*;
 use crate::ui::widget::widget::*;
 use crate::util::time::sec_to_str;
+use einstein_puzzle::difficulty::Difficulty;
+use einstein_puzzle::rules::PuzzleSize;
 use sdl2::keyboard::Keycode;
 use sdl2::pixels::Color;
+use std::cell::Cell;
+use std::rc::Rc;
+
+fn create_scores_table(scores: &[Score], highlight: Option<usize>) -> Container<()> {
+    let mut grid = GridBuilder::new(Container::container(Size::new(300, 250), None, None), 3, 1);
+    for (i, score) in scores.iter().enumerate() {
+        let color = if highlight == Some(i) {
+            Color::RGB(255, 255, 0)
+        } else {
+            Color::RGB(255, 255, 255)
+        };
+
+        grid = grid
+            .add(
+                0,
+                i,
+                Label::new(
+                    Size::new(30, 25),
+                    &format!("{}.", i + 1),
+                    color,
+                    HorizontalAlign::Right,
+                )
+                .no_action(),
+            )
+            .add(
+                1,
+                i,
+                Label::new(
+                    Size::new(160, 25),
+                    &score.name,
+                    color,
+                    HorizontalAlign::Left,
+                )
+                .no_action(),
+            )
+            .add(
+                2,
+                i,
+                Label::new(
+                    Size::new(80, 25),
+                    &sec_to_str(score.score),
+                    color,
+                    HorizontalAlign::Right,
+                )
+                .no_action(),
+            );
+    }
+    grid.build()
+}
 
 pub fn create_topscores_dialog(
     scores: &Scores,
+    table: ScoreTable,
     messages: &Messages,
     highlight: Option<usize>,
 ) -> Container<()> {
     let theme = DialogTheme::Blue;
 
-    let container = dialog_container(Size::new(320, 350), theme)
-        .add(
-            10,
-            10,
-            Label::title(Size::new(300, 40), messages.top_scores).no_action(),
-        )
-        .add(10, 50, {
-            let mut grid =
-                GridBuilder::new(Container::container(Size::new(300, 250), None, None), 3, 1);
-            for (i, score) in scores.0.iter().enumerate() {
-                let color = if highlight == Some(i) {
-                    Color::RGB(255, 255, 0)
-                } else {
-                    Color::RGB(255, 255, 255)
-                };
+    let mut container = dialog_container(Size::new(320, 420), theme);
+    container.push(
+        10,
+        10,
+        Label::title(Size::new(300, 40), messages.top_scores).no_action(),
+    );
+
+    let sizes: Vec<PuzzleSize> = GamePrivate::supported_sizes().collect();
+    let selected = Rc::new(Cell::new(table));
+    let tables: Vec<(ScoreTable, Rc<RefCell<Option<()>>>)> = sizes
+        .iter()
+        .flat_map(|size| {
+            Difficulty::ALL.iter().map(|difficulty| ScoreTable {
+                size: *size,
+                difficulty: *difficulty,
+            })
+        })
+        .map(|t| (t, Rc::new(RefCell::new((t == table).then_some(())))))
+        .collect();
 
-                grid = grid
-                    .add(
-                        0,
-                        i,
-                        Label::new(
-                            Size::new(30, 25),
-                            &format!("{}.", i + 1),
-                            color,
-                            HorizontalAlign::Right,
-                        )
-                        .no_action(),
-                    )
-                    .add(
-                        1,
-                        i,
-                        Label::new(
-                            Size::new(160, 25),
-                            &score.name,
-                            color,
-                            HorizontalAlign::Left,
-                        )
-                        .no_action(),
-                    )
-                    .add(
-                        2,
-                        i,
-                        Label::new(
-                            Size::new(80, 25),
-                            &sec_to_str(score.score),
-                            color,
-                            HorizontalAlign::Right,
-                        )
-                        .no_action(),
-                    );
+    let show_selected = {
+        let selected = selected.clone();
+        let tables = tables.clone();
+        move || {
+            for (t, trigger) in &tables {
+                *trigger.borrow_mut() = (*t == selected.get()).then_some(());
             }
-            grid.build()
+        }
+    };
+
+    container.push(60, 50, {
+        let selected2 = selected.clone();
+        let show_selected = show_selected.clone();
+        let labels = sizes
+            .iter()
+            .map(|size| size_name(*size, messages))
+            .collect();
+        let index = sizes
+            .iter()
+            .position(|size| *size == table.size)
+            .unwrap_or_default();
+        let sizes = sizes.clone();
+        dialog_choice(theme, Size::new(200, 26), labels, index).flat_map_action(move |index, _| {
+            selected2.set(ScoreTable {
+                size: sizes[*index],
+                ..selected2.get()
+            });
+            show_selected();
+            Ok(EventReaction::update())
+        })
+    });
+
+    container.push(60, 82, {
+        let selected2 = selected.clone();
+        let labels = Difficulty::ALL
+            .iter()
+            .map(|d| messages.difficulty_name(*d).to_string())
+            .collect();
+        let index = Difficulty::ALL
+            .iter()
+            .position(|d| *d == table.difficulty)
+            .unwrap_or_default();
+        dialog_choice(theme, Size::new(200, 26), labels, index).flat_map_action(move |index, _| {
+            selected2.set(ScoreTable {
+                difficulty: Difficulty::ALL[*index],
+                ..selected2.get()
+            });
+            show_selected();
+            Ok(EventReaction::update())
         })
-        .add(
-            115,
-            310,
-            DialogButton::new(
-                Size::new(90, 25),
-                theme,
-                messages.ok,
-                &[Keycode::Escape, Keycode::Return],
-                (),
-            ),
+    });
+
+    for (t, trigger) in tables {
+        let entries: Vec<Score> = scores.table(t).cloned().collect();
+        let highlight = if t == table { highlight } else { None };
+        container.push(
+            10,
+            120,
+            ConditionalWidget::new(trigger, move |_| create_scores_table(&entries, highlight)),
         );
+    }
+
+    container.push(
+        115,
+        380,
+        DialogButton::new(
+            Size::new(90, 25),
+            theme,
+            messages.ok,
+            &[Keycode::Escape, Keycode::Return],
+            (),
+        ),
+    );
 
     dialog_widget(None, container)
 }
@@ -48,7 +48,15 @@ impl Context for AppContext<'_> {
             .map_err(|e| format_err!("{}", e))?;
         loop {
             sleep(Duration::from_millis(5));
-            widget.on_event(&WidgetEvent::Tick, self)?; // TODO: add timer
+            let reaction = widget.on_event(&WidgetEvent::Tick, self)?; // TODO: add timer
+            if reaction.update {
+                let b = widget.draw(self.resource_manager);
+                b.draw(*self.canvas.borrow_mut(), 0, 0, self.resource_manager)?;
+                self.canvas.borrow_mut().present();
+            }
+            if reaction.action.is_some() {
+                return Ok(());
+            }
 
             for event in event_pump.poll_iter() {
                 let reaction = match event {
@@ -0,0 +1,104 @@
+use crate::error::format_err;
+use crate::resources::audio::CLICK;
+use crate::resources::manager::ResourceManager;
+use crate::ui::brick::*;
+use crate::ui::common::{Rect, Size};
+use crate::ui::context::Context;
+use crate::ui::widget::widget::*;
+use sdl2::mouse::MouseButton;
+use std::cell::Cell;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ChoiceArrow {
+    Prev,
+    Next,
+}
+
+type DrawChoice = dyn Fn(Size, &str, Option<ChoiceArrow>, &dyn ResourceManager) -> Brick;
+
+pub struct Choice {
+    size: Size,
+    labels: Vec<String>,
+    selected: Cell<usize>,
+    highlighted: Cell<Option<ChoiceArrow>>,
+    draw: Box<DrawChoice>,
+}
+
+impl Choice {
+    pub fn new(
+        size: Size,
+        labels: Vec<String>,
+        selected: usize,
+        draw: impl Fn(Size, &str, Option<ChoiceArrow>, &dyn ResourceManager) -> Brick + 'static,
+    ) -> Self {
+        Self {
+            size,
+            labels,
+            selected: Cell::new(selected),
+            highlighted: Cell::new(None),
+            draw: Box::new(draw),
+        }
+    }
+
+    pub fn arrow_rect(size: Size, arrow: ChoiceArrow) -> Rect {
+        match arrow {
+            ChoiceArrow::Prev => Rect::new(0, 0, size.height, size.height),
+            ChoiceArrow::Next => Rect::new(
+                size.width.saturating_sub(size.height) as i32,
+                0,
+                size.height,
+                size.height,
+            ),
+        }
+    }
+
+    fn find_arrow(&self, x: i32, y: i32) -> Option<ChoiceArrow> {
+        [ChoiceArrow::Prev, ChoiceArrow::Next]
+            .into_iter()
+            .find(|arrow| Self::arrow_rect(self.size, *arrow).contains_point((x, y)))
+    }
+}
+
+impl Widget<usize> for Choice {
+    fn get_size(&self) -> Size {
+        self.size
+    }
+
+    fn on_event(&mut self, event: &Event, context: &dyn Context) -> EventResult<usize> {
+        match *event {
+            Event::MouseButtonDown(MouseButton::Left, x, y) => {
+                let count = self.labels.len();
+                let selected = match self.find_arrow(x, y) {
+                    Some(ChoiceArrow::Prev) => (self.selected.get() + count - 1) % count,
+                    Some(ChoiceArrow::Next) => (self.selected.get() + 1) % count,
+                    None => return Ok(EventReaction::empty()),
+                };
+                context
+                    .audio()
+                    .play(&context.resource_manager().chunk(&CLICK))
+                    .map_err(|e| format_err!("{}", e))?;
+                self.selected.set(selected);
+                Ok(EventReaction::update_and_action(selected))
+            }
+            Event::MouseMove(x, y) => {
+                let to_highlight = self.find_arrow(x, y);
+                if self.highlighted.get() != to_highlight {
+                    self.highlighted.set(to_highlight);
+                    Ok(EventReaction::update())
+                } else {
+                    Ok(EventReaction::empty())
+                }
+            }
+            _ => Ok(EventReaction::empty()),
+        }
+    }
+
+    fn draw(&self, resource_manager: &dyn ResourceManager) -> Brick {
+        (self.draw)(
+            self.size,
+            &self.labels[self.selected.get()],
+            self.highlighted.get(),
+            resource_manager,
+        )
+    }
+}
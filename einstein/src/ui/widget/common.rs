@@ -12,6 +12,7 @@ pub enum Border {
 pub enum Background {
     Color(Color),
     Image(&'static Resource, Option<Rect>),
+    Stretched(&'static Resource, Option<Rect>),
 }
 
 #[derive(Clone, Copy)]
@@ -1,6 +1,7 @@
 pub mod any_key;
 pub mod button;
 pub mod checkbox;
+pub mod choice;
 pub mod common;
 pub mod conditional;
 pub mod container;
@@ -0,0 +1,157 @@
+use crate::error::*;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Self {
+        Self { year, month, day }
+    }
+
+    #[cfg(unix)]
+    pub fn today() -> Self {
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        unsafe {
+            let now = libc::time(std::ptr::null_mut());
+            libc::localtime_r(&now, &mut tm);
+        }
+        Self::new(tm.tm_year + 1900, (tm.tm_mon + 1) as u32, tm.tm_mday as u32)
+    }
+
+    #[cfg(windows)]
+    pub fn today() -> Self {
+        use winapi::um::minwinbase::SYSTEMTIME;
+        use winapi::um::sysinfoapi::GetLocalTime;
+
+        let mut st: SYSTEMTIME = unsafe { std::mem::zeroed() };
+        unsafe { GetLocalTime(&mut st) };
+        Self::new(st.wYear as i32, st.wMonth as u32, st.wDay as u32)
+    }
+
+    // Days since 1970-01-01 (Howard Hinnant's days_from_civil).
+    pub fn days(self) -> i64 {
+        let year = if self.month <= 2 {
+            self.year as i64 - 1
+        } else {
+            self.year as i64
+        };
+        let era = year.div_euclid(400);
+        let yoe = year.rem_euclid(400);
+        let month = self.month as i64;
+        let doy =
+            (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + self.day as i64 - 1;
+        let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+        era * 146097 + doe - 719468
+    }
+
+    pub fn from_days(days: i64) -> Self {
+        let z = days + 719468;
+        let era = z.div_euclid(146097);
+        let doe = z.rem_euclid(146097);
+        let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Self::new(year, month, day)
+    }
+
+    pub fn add_days(self, days: i64) -> Self {
+        Self::from_days(self.days() + days)
+    }
+
+    /// Day of week, starting with Monday as 0.
+    pub fn weekday(self) -> u32 {
+        (self.days() + 3).rem_euclid(7) as u32
+    }
+
+    pub fn first_of_month(self) -> Self {
+        Self::new(self.year, self.month, 1)
+    }
+
+    pub fn days_in_month(self) -> u32 {
+        let next = if self.month == 12 {
+            Self::new(self.year + 1, 1, 1)
+        } else {
+            Self::new(self.year, self.month + 1, 1)
+        };
+        (next.days() - self.first_of_month().days()) as u32
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut parts = s.splitn(3, '-');
+        let mut next = || {
+            parts
+                .next()
+                .ok_or_else(|| format_err!("Invalid date {}", s))
+        };
+        let year = next()?.parse()?;
+        let month = next()?.parse()?;
+        let day = next()?.parse()?;
+        let date = Self::new(year, month, day);
+        if !(1..=12).contains(&month) || day < 1 || day > date.days_in_month() {
+            return Err(format_err!("Invalid date {}", s));
+        }
+        Ok(date)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = anyhow::Error;
+
+    fn try_from(value: String) -> Result<Self> {
+        value.parse()
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> Self {
+        date.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_days() {
+        assert_eq!(Date::new(1970, 1, 1).days(), 0);
+        assert_eq!(Date::new(2000, 3, 1).days(), 11017);
+        assert_eq!(Date::from_days(11017), Date::new(2000, 3, 1));
+        assert_eq!(Date::new(2024, 2, 28).add_days(1), Date::new(2024, 2, 29));
+        assert_eq!(Date::new(2023, 12, 31).add_days(1), Date::new(2024, 1, 1));
+        assert_eq!(Date::new(2024, 2, 1).days_in_month(), 29);
+        assert_eq!(Date::new(1970, 1, 1).weekday(), 3);
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            "2024-05-17".parse::<Date>().unwrap(),
+            Date::new(2024, 5, 17)
+        );
+        assert_eq!(Date::new(2024, 5, 17).to_string(), "2024-05-17");
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("2023-13".parse::<Date>().is_err());
+    }
+}
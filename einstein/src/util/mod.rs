@@ -1,2 +1,3 @@
+pub mod date;
 pub mod group_by_weight;
 pub mod time;